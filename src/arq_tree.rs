@@ -152,7 +152,7 @@ impl ArqSpec for AssignMin {
         (*a).min(*b)
     }
     fn identity() -> Self::M {
-        Self::M::max_value()
+        Self::M::MAX
     }
 }
 
@@ -0,0 +1,140 @@
+//! Vertex orderings and colorings of undirected graphs.
+use super::Graph;
+
+impl Graph {
+    /// Matula-Beck algorithm: repeatedly removes a vertex of minimum remaining
+    /// degree, returning the removal order and the degeneracy, i.e., the
+    /// largest degree seen at removal time. Every vertex then has at most that
+    /// many neighbors later in the order, and the degeneracy equals the
+    /// largest k for which the graph has a nonempty k-core. Uses bucket queues
+    /// in O(V + E).
+    pub fn degeneracy_ordering(&self) -> (Vec<usize>, usize) {
+        let n = self.num_v();
+        let mut degree = (0..n).map(|u| self.adj_list(u).count()).collect::<Vec<_>>();
+        // Buckets may hold stale entries for vertices whose degree has since
+        // dropped; these are skipped when popped.
+        let mut buckets = vec![vec![]; self.num_e() + 1];
+        for u in 0..n {
+            buckets[degree[u]].push(u);
+        }
+        let mut removed = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        let (mut cur, mut degeneracy) = (0, 0);
+        while order.len() < n {
+            let Some(u) = buckets[cur].pop() else {
+                cur += 1;
+                continue;
+            };
+            if removed[u] || degree[u] != cur {
+                continue;
+            }
+            removed[u] = true;
+            order.push(u);
+            degeneracy = degeneracy.max(cur);
+            for (_, v) in self.adj_list(u) {
+                if !removed[v] {
+                    degree[v] -= 1;
+                    buckets[degree[v]].push(v);
+                    cur = cur.min(degree[v]);
+                }
+            }
+        }
+        (order, degeneracy)
+    }
+
+    /// Colors the vertices in the given order, which must list each exactly
+    /// once, giving each the smallest color not taken by an earlier neighbor.
+    /// Colors are numbered from 0. Following degeneracy_ordering() in reverse,
+    /// each vertex has at most degeneracy earlier neighbors, so at most
+    /// degeneracy + 1 colors are used. O(V + E).
+    pub fn greedy_coloring(&self, order: &[usize]) -> Vec<usize> {
+        assert_eq!(self.num_v(), order.len());
+        let mut color = vec![None; self.num_v()];
+        // taken[c] == Some(u) iff a neighbor of u has color c.
+        let mut taken = vec![None; self.num_v()];
+        for &u in order {
+            assert!(
+                color[u].is_none(),
+                "vertex {} appears twice in the order",
+                u
+            );
+            for (_, v) in self.adj_list(u) {
+                if let Some(c) = color[v] {
+                    taken[c] = Some(u);
+                }
+            }
+            color[u] = (0..).find(|&c| taken[c] != Some(u));
+        }
+        color.into_iter().map(Option::unwrap).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_degeneracy_ordering() {
+        // A 4-clique on 0..4, with a path 3-4-5 and a triangle 5-6-7 hanging
+        // off it, is 3-degenerate.
+        let mut graph = Graph::new(8, 24);
+        for u in 0..4 {
+            for v in u + 1..4 {
+                graph.add_undirected_edge(u, v);
+            }
+        }
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 6);
+        graph.add_undirected_edge(6, 7);
+        graph.add_undirected_edge(7, 5);
+
+        let (order, degeneracy) = graph.degeneracy_ordering();
+        assert_eq!(degeneracy, 3);
+        let mut pos = [0; 8];
+        for (i, &u) in order.iter().enumerate() {
+            pos[u] = i;
+        }
+        for u in 0..8 {
+            let later = graph.adj_list(u).filter(|&(_, v)| pos[v] > pos[u]);
+            assert!(later.count() <= degeneracy);
+        }
+
+        let mut tree = Graph::new(3, 4);
+        tree.add_undirected_edge(0, 1);
+        tree.add_undirected_edge(1, 2);
+        assert_eq!(tree.degeneracy_ordering().1, 1);
+        assert_eq!(Graph::new(2, 0).degeneracy_ordering(), (vec![1, 0], 0));
+    }
+
+    #[test]
+    fn test_greedy_coloring() {
+        let mut cycle = Graph::new(6, 12);
+        for u in 0..6 {
+            cycle.add_undirected_edge(u, (u + 1) % 6);
+        }
+        assert_eq!(
+            cycle.greedy_coloring(&[0, 1, 2, 3, 4, 5]),
+            vec![0, 1, 0, 1, 0, 1]
+        );
+        // Coloring 0 and 3 first forces a third color.
+        assert_eq!(
+            cycle.greedy_coloring(&[0, 3, 1, 2, 4, 5]),
+            vec![0, 1, 2, 0, 1, 2]
+        );
+
+        // Reverse degeneracy order needs at most degeneracy + 1 colors.
+        let mut wheel = Graph::new(6, 20);
+        for u in 1..6 {
+            wheel.add_undirected_edge(0, u);
+            wheel.add_undirected_edge(u, u % 5 + 1);
+        }
+        let (mut order, degeneracy) = wheel.degeneracy_ordering();
+        order.reverse();
+        let color = wheel.greedy_coloring(&order);
+        assert!(color.iter().all(|&c| c <= degeneracy));
+        for u in 0..6 {
+            assert!(wheel.adj_list(u).all(|(_, v)| color[u] != color[v]));
+        }
+    }
+}
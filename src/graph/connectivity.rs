@@ -36,6 +36,186 @@ impl ConnectivityData {
     }
 }
 
+/// Tarjan's algorithm: labels the strongly connected components of a directed
+/// graph, so that two vertices have the same label iff they are mutually
+/// reachable. Labels are numbered from 0 in reverse topological order of the
+/// condensation. The explicit stack avoids overflow on deep graphs.
+pub fn scc(graph: &Graph) -> Vec<usize> {
+    let mut data = ConnectivityData::new(graph.num_v());
+    let mut comp = vec![None; graph.num_v()];
+    let mut num_scc = 0;
+    for root in 0..graph.num_v() {
+        if data.vis[root] != 0 {
+            continue;
+        }
+        data.visit(root);
+        let mut stack = vec![(root, graph.adj_list(root))];
+        while let Some((u, adj)) = stack.last_mut() {
+            let u = *u;
+            if let Some((_, v)) = adj.next() {
+                if data.vis[v] == 0 {
+                    data.visit(v);
+                    stack.push((v, graph.adj_list(v)));
+                } else if comp[v].is_none() {
+                    data.lower(u, data.vis[v]);
+                }
+            } else {
+                stack.pop();
+                if let Some(&(par, _)) = stack.last() {
+                    data.lower(par, data.low[u]);
+                }
+                if data.vis[u] == data.low[u] {
+                    while let Some(v) = data.v_stack.pop() {
+                        comp[v] = Some(num_scc);
+                        if v == u {
+                            break;
+                        }
+                    }
+                    num_scc += 1;
+                }
+            }
+        }
+    }
+    comp.into_iter().map(Option::unwrap).collect()
+}
+
+/// In an undirected graph, finds both the bridges, as in bridges(), and the
+/// articulation points, as in articulation_points(), with a single DFS.
+pub fn cut_analysis(graph: &Graph) -> (Vec<usize>, Vec<bool>) {
+    let cg = ConnectivityGraph::new(graph, false);
+    let bridges = (0..graph.num_e()).filter(|&e| cg.is_cut_edge(e)).collect();
+    let cut_vertices = (0..graph.num_v()).map(|u| cg.is_cut_vertex(u)).collect();
+    (bridges, cut_vertices)
+}
+
+/// In an undirected graph, finds the ids of all edges that are bridges. Both
+/// directions e and e^1 of each bridge are included.
+pub fn bridges(graph: &Graph) -> Vec<usize> {
+    cut_analysis(graph).0
+}
+
+/// In an undirected graph, finds the endpoints (u, v) of every bridge. Each
+/// bridge appears once, oriented along its even-numbered direction.
+pub fn bridge_endpoints(graph: &Graph) -> Vec<(usize, usize)> {
+    bridges(graph)
+        .into_iter()
+        .filter(|&e| e % 2 == 0)
+        .map(|e| (graph.endp[e ^ 1], graph.endp[e]))
+        .collect()
+}
+
+/// In an undirected graph, determines whether u and v stay connected after
+/// removing any one edge, i.e., whether they're in the same 2-edge-connected
+/// component.
+pub fn are_2_edge_connected(graph: &Graph, u: usize, v: usize) -> bool {
+    let cg = ConnectivityGraph::new(graph, false);
+    cg.cc[u] == cg.cc[v]
+}
+
+/// In an undirected graph, determines which vertices are articulation points.
+pub fn articulation_points(graph: &Graph) -> Vec<bool> {
+    cut_analysis(graph).1
+}
+
+/// Decomposition of an undirected cactus graph, in which every edge lies on
+/// at most one simple cycle. As in min_spanning_tree(), undirected edge i
+/// consists of the directed edges 2i and 2i+1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct CactusInfo {
+    /// The edges of each simple cycle, in order around it.
+    pub cycles: Vec<Vec<usize>>,
+    /// The edges that lie on no cycle, in increasing order.
+    pub bridges: Vec<usize>,
+    /// The index in cycles of the cycle containing each edge, if any.
+    pub cycle_of: Vec<Option<usize>>,
+}
+
+impl Graph {
+    /// Solves the 2-SAT problem whose implication graph was built with
+    /// add_two_sat_clause(): returns a satisfying assignment of the num_v() / 2
+    /// variables, or None if there is none. A variable is true iff its positive
+    /// literal's SCC comes after its negation's in topological order.
+    pub fn two_sat(&self) -> Option<Vec<bool>> {
+        let comp = scc(self);
+        (0..self.num_v() / 2)
+            .map(|i| {
+                let (scc_true, scc_false) = (comp[2 * i], comp[2 * i + 1]);
+                if scc_true == scc_false {
+                    None
+                } else {
+                    Some(scc_true < scc_false)
+                }
+            })
+            .collect()
+    }
+
+    /// Decomposes an undirected graph into its cycles and bridges, or returns
+    /// None if it isn't a cactus. Every non-tree edge of a DFS forest closes a
+    /// cycle with the tree path it spans; the graph is a cactus iff no tree
+    /// edge is covered by two such cycles. Self-loops and parallel edges form
+    /// cycles of length 1 and 2. O(V + E).
+    pub fn cactus_decomposition(&self) -> Option<CactusInfo> {
+        self.assert_undirected();
+        let mut cycles = vec![];
+        let mut cycle_of = vec![None; self.num_e() / 2];
+        let mut depth = vec![None; self.num_v()];
+        let mut parent = vec![None; self.num_v()];
+        for root in 0..self.num_v() {
+            if depth[root].is_some() {
+                continue;
+            }
+            depth[root] = Some(0);
+            let mut stack = vec![(root, self.adj_list(root))];
+            while let Some((u, adj)) = stack.last_mut() {
+                let u = *u;
+                let Some((e, v)) = adj.next() else {
+                    stack.pop();
+                    continue;
+                };
+                match (depth[u], depth[v]) {
+                    (Some(d), None) => {
+                        depth[v] = Some(d + 1);
+                        parent[v] = Some(e);
+                        stack.push((v, self.adj_list(v)));
+                    }
+                    // Each non-tree edge is handled from its lower endpoint,
+                    // or from its even direction if it's a self-loop.
+                    (Some(du), Some(dv))
+                        if parent[u] != Some(e ^ 1) && (dv < du || (v == u && e % 2 == 0)) =>
+                    {
+                        let id = cycles.len();
+                        let mut cycle = vec![e / 2];
+                        let mut w = u;
+                        while w != v {
+                            let pe = parent[w].unwrap();
+                            if cycle_of[pe / 2].is_some() {
+                                return None;
+                            }
+                            cycle.push(pe / 2);
+                            w = self.endp[pe ^ 1];
+                        }
+                        for &f in &cycle {
+                            cycle_of[f] = Some(id);
+                        }
+                        cycle[1..].reverse();
+                        cycle.rotate_left(1);
+                        cycles.push(cycle);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let bridges = (0..cycle_of.len())
+            .filter(|&f| cycle_of[f].is_none())
+            .collect();
+        Some(CactusInfo {
+            cycles,
+            bridges,
+            cycle_of,
+        })
+    }
+}
+
 /// Represents the decomposition of a graph into any of its constituent parts:
 ///
 /// - Connected components (CC),
@@ -64,8 +244,11 @@ impl<'a> ConnectivityGraph<'a> {
     /// - is_directed == true on directed graph: SCCs in rev-topological order
     /// - is_directed == true on undirected graph: CCs
     /// - is_directed == false on undirected graph: 2ECCs and 2VCCs
-    /// - is_directed == false on directed graph: undefined behavior
+    /// - is_directed == false on directed graph: panics
     pub fn new(graph: &'a Graph, is_directed: bool) -> Self {
+        if !is_directed {
+            graph.assert_undirected();
+        }
         let mut connect = Self {
             graph,
             cc: vec![0; graph.num_v()],
@@ -208,6 +391,28 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_scc() {
+        let mut graph = Graph::new(4, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 0);
+        graph.add_edge(2, 3);
+
+        assert_eq!(scc(&graph), vec![1, 1, 1, 0]);
+    }
+
+    #[test]
+    fn test_scc_long_chain() {
+        let n = 500_000;
+        let mut graph = Graph::new(n, n - 1);
+        for u in 1..n {
+            graph.add_edge(u - 1, u);
+        }
+        let comp = scc(&graph);
+        assert!((0..n).all(|u| comp[u] == n - 1 - u));
+    }
+
     #[test]
     fn test_two_sat() {
         let mut graph = Graph::new(6, 8);
@@ -225,6 +430,95 @@ mod test {
         assert_eq!(ConnectivityGraph::new(&graph, true).two_sat_assign(), None);
     }
 
+    #[test]
+    fn test_graph_two_sat() {
+        // (x0 || x1) && (!x0 || x1)
+        let mut graph = Graph::new(4, 4);
+        graph.add_two_sat_clause(0, 2);
+        graph.add_two_sat_clause(1, 2);
+        let assignment = graph.two_sat().unwrap();
+        assert_eq!(assignment.len(), 2);
+        // Literal 2i is the variable xi, while 2i+1 is its negation.
+        let holds = |lit: usize| assignment[lit / 2] == lit.is_multiple_of(2);
+        assert!(holds(0) || holds(2));
+        assert!(holds(1) || holds(2));
+
+        // (!x0 || !x0) && (x0 || x1) forces x0 false and x1 true.
+        let mut graph = Graph::new(4, 4);
+        graph.add_two_sat_clause(1, 1);
+        graph.add_two_sat_clause(0, 2);
+        assert_eq!(graph.two_sat(), Some(vec![false, true]));
+
+        // x0 && !x0
+        let mut graph = Graph::new(2, 4);
+        graph.add_two_sat_clause(0, 0);
+        graph.add_two_sat_clause(1, 1);
+        assert_eq!(graph.two_sat(), None);
+    }
+
+    #[test]
+    fn test_bridges_and_articulation_points() {
+        let mut path = Graph::new(4, 6);
+        path.add_undirected_edge(0, 1);
+        path.add_undirected_edge(1, 2);
+        path.add_undirected_edge(2, 3);
+        assert_eq!(bridges(&path), (0..6).collect::<Vec<_>>());
+        assert_eq!(articulation_points(&path), vec![false, true, true, false]);
+
+        let mut cycle = Graph::new(4, 8);
+        cycle.add_undirected_edge(0, 1);
+        cycle.add_undirected_edge(1, 2);
+        cycle.add_undirected_edge(2, 3);
+        cycle.add_undirected_edge(3, 0);
+        assert!(bridges(&cycle).is_empty());
+        assert_eq!(articulation_points(&cycle), vec![false; 4]);
+    }
+
+    /// Triangles 0-1-2 and 3-4-5, joined by the bridge 2-3.
+    fn two_triangles() -> Graph {
+        let mut graph = Graph::new(6, 14);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 5);
+        graph.add_undirected_edge(5, 3);
+        graph
+    }
+
+    #[test]
+    fn test_bridge_endpoints() {
+        let graph = two_triangles();
+        assert_eq!(bridge_endpoints(&graph), vec![(2, 3)]);
+    }
+
+    #[test]
+    fn test_are_2_edge_connected() {
+        let graph = two_triangles();
+        assert!(are_2_edge_connected(&graph, 0, 2));
+        assert!(are_2_edge_connected(&graph, 5, 3));
+        assert!(!are_2_edge_connected(&graph, 2, 3));
+        assert!(!are_2_edge_connected(&graph, 0, 4));
+    }
+
+    #[test]
+    fn test_cut_analysis() {
+        // Triangle 0-1-2 with a path 2-3-4 hanging off it.
+        let mut graph = Graph::new(5, 10);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 4);
+
+        let (bridge_ids, cut_vertices) = cut_analysis(&graph);
+        assert_eq!(bridge_ids, vec![6, 7, 8, 9]);
+        assert_eq!(cut_vertices, vec![false, false, true, true, false]);
+        assert_eq!(bridge_ids, bridges(&graph));
+        assert_eq!(cut_vertices, articulation_points(&graph));
+    }
+
     #[test]
     fn test_biconnected() {
         let mut graph = Graph::new(3, 6);
@@ -243,4 +537,57 @@ mod test {
         assert_eq!(bridges, vec![0, 1]);
         assert_eq!(articulation_points, vec![1]);
     }
+
+    #[test]
+    fn test_cactus_decomposition() {
+        let mut tree = Graph::new(4, 6);
+        tree.add_undirected_edge(0, 1);
+        tree.add_undirected_edge(1, 2);
+        tree.add_undirected_edge(1, 3);
+        let info = tree.cactus_decomposition().unwrap();
+        assert!(info.cycles.is_empty());
+        assert_eq!(info.bridges, vec![0, 1, 2]);
+
+        let mut cycle = Graph::new(3, 6);
+        cycle.add_undirected_edge(0, 1);
+        cycle.add_undirected_edge(1, 2);
+        cycle.add_undirected_edge(2, 0);
+        let info = cycle.cactus_decomposition().unwrap();
+        assert_eq!(info.cycles.len(), 1);
+        let mut edges = info.cycles[0].clone();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![0, 1, 2]);
+        assert!(info.bridges.is_empty());
+
+        // Triangles 0-1-2 and 2-3-4 share vertex 2; a double edge 4-5 forms a
+        // 2-cycle, and 5-6 is a bridge.
+        let mut bowtie = Graph::new(7, 16);
+        bowtie.add_undirected_edge(0, 1);
+        bowtie.add_undirected_edge(1, 2);
+        bowtie.add_undirected_edge(2, 0);
+        bowtie.add_undirected_edge(2, 3);
+        bowtie.add_undirected_edge(3, 4);
+        bowtie.add_undirected_edge(4, 2);
+        bowtie.add_undirected_edge(4, 5);
+        bowtie.add_undirected_edge(5, 4);
+        bowtie.add_undirected_edge(5, 6);
+        let info = bowtie.cactus_decomposition().unwrap();
+        assert_eq!(info.cycles.len(), 3);
+        assert_eq!(info.bridges, vec![8]);
+        for (id, cycle) in info.cycles.iter().enumerate() {
+            assert!(cycle.iter().all(|&f| info.cycle_of[f] == Some(id)));
+        }
+        assert_eq!(info.cycle_of[0], info.cycle_of[2]);
+        assert_ne!(info.cycle_of[0], info.cycle_of[3]);
+        assert_eq!(info.cycle_of[6], info.cycle_of[7]);
+
+        // Two triangles sharing the edge 0-2.
+        let mut theta = Graph::new(4, 10);
+        theta.add_undirected_edge(0, 1);
+        theta.add_undirected_edge(1, 2);
+        theta.add_undirected_edge(2, 0);
+        theta.add_undirected_edge(2, 3);
+        theta.add_undirected_edge(3, 0);
+        assert_eq!(theta.cactus_decomposition(), None);
+    }
 }
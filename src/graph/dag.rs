@@ -0,0 +1,478 @@
+//! Algorithms on directed acyclic graphs.
+use super::flow::FlowGraph;
+use super::Graph;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+impl Graph {
+    /// Returns the number of edges pointing into each vertex.
+    fn in_degrees(&self) -> Vec<usize> {
+        let mut in_degree = vec![0; self.num_v()];
+        for &v in &self.endp {
+            in_degree[v] += 1;
+        }
+        in_degree
+    }
+
+    /// Kahn's algorithm: orders the vertices so that every edge points forward,
+    /// or returns None if the graph has a cycle. Ties are broken by taking the
+    /// smallest vertex of in-degree zero first, as in topological_sort_lex().
+    /// O(V log V + E).
+    pub fn topological_sort(&self) -> Option<Vec<usize>> {
+        self.topological_sort_lex()
+    }
+
+    /// Returns the lexicographically smallest topological order, by always
+    /// taking the smallest available vertex, or None if the graph has a cycle.
+    /// O(V log V + E).
+    pub fn topological_sort_lex(&self) -> Option<Vec<usize>> {
+        let mut in_degree = self.in_degrees();
+        let mut heap = (0..self.num_v())
+            .filter(|&u| in_degree[u] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(self.num_v());
+        while let Some(Reverse(u)) = heap.pop() {
+            order.push(u);
+            for (_, v) in self.adj_list(u) {
+                in_degree[v] -= 1;
+                if in_degree[v] == 0 {
+                    heap.push(Reverse(v));
+                }
+            }
+        }
+        if order.len() == self.num_v() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+
+    /// Determines whether the graph has exactly one topological order, which
+    /// happens iff Kahn's algorithm never has a choice between two vertices of
+    /// in-degree zero; equivalently, iff the DAG has a Hamiltonian path.
+    /// Returns false if the graph has a cycle. O(V + E).
+    pub fn has_unique_toposort(&self) -> bool {
+        let mut in_degree = self.in_degrees();
+        let mut ready = (0..self.num_v())
+            .filter(|&u| in_degree[u] == 0)
+            .collect::<Vec<_>>();
+        let mut num_sorted = 0;
+        while let Some(u) = ready.pop() {
+            if !ready.is_empty() {
+                return false;
+            }
+            num_sorted += 1;
+            for (_, v) in self.adj_list(u) {
+                in_degree[v] -= 1;
+                if in_degree[v] == 0 {
+                    ready.push(v);
+                }
+            }
+        }
+        num_sorted == self.num_v()
+    }
+
+    /// Groups the vertices of a DAG into levels, such that every edge points
+    /// into a later level and each level is as early as possible: level 0 holds
+    /// the vertices of in-degree zero, and so on. Tasks in the same level can
+    /// run in parallel. Returns None if the graph has a cycle. O(V + E).
+    pub fn parallel_schedule(&self) -> Option<Vec<Vec<usize>>> {
+        let mut in_degree = self.in_degrees();
+        let mut level = (0..self.num_v())
+            .filter(|&u| in_degree[u] == 0)
+            .collect::<Vec<_>>();
+        let (mut levels, mut num_scheduled) = (vec![], 0);
+        while !level.is_empty() {
+            let mut next_level = vec![];
+            for &u in &level {
+                for (_, v) in self.adj_list(u) {
+                    in_degree[v] -= 1;
+                    if in_degree[v] == 0 {
+                        next_level.push(v);
+                    }
+                }
+            }
+            num_scheduled += level.len();
+            levels.push(level);
+            level = next_level;
+        }
+        if num_scheduled == self.num_v() {
+            Some(levels)
+        } else {
+            None
+        }
+    }
+
+    /// Finds a maximum-weight path in a DAG, which may start and end anywhere,
+    /// as a pair of total weight and edge sequence. Returns None if the graph
+    /// has a cycle. Negative weights are allowed. O(V + E).
+    pub fn dag_longest_path(&self, weights: &[i64]) -> Option<(i64, Vec<usize>)> {
+        assert_eq!(self.num_e(), weights.len());
+        let mut best = vec![0; self.num_v()];
+        let mut pred = vec![None; self.num_v()];
+        for u in self.topological_sort()? {
+            for (e, v) in self.adj_list(u) {
+                if best[v] < best[u] + weights[e] {
+                    best[v] = best[u] + weights[e];
+                    pred[v] = Some(e);
+                }
+            }
+        }
+        Some(match (0..self.num_v()).max_by_key(|&v| best[v]) {
+            Some(v) => (best[v], self.path_from_pred(&pred, v)),
+            None => (0, vec![]),
+        })
+    }
+
+    /// Counts the paths from source to target in a DAG, modulo the modulus if
+    /// one is given. Returns None if the graph has a cycle. O(V + E).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus is 0, or if none is given and the count overflows
+    /// a u64.
+    pub fn dag_count_paths(
+        &self,
+        source: usize,
+        target: usize,
+        modulus: Option<u64>,
+    ) -> Option<u64> {
+        assert_ne!(modulus, Some(0), "modulus must be positive");
+        let mut count = vec![0u64; self.num_v()];
+        count[source] = modulus.map_or(1, |m| 1 % m);
+        for u in self.topological_sort()? {
+            for (_, v) in self.adj_list(u) {
+                count[v] = match modulus {
+                    Some(m) => (count[v] + count[u]) % m,
+                    None => count[v].checked_add(count[u]).expect("path count overflow"),
+                };
+            }
+        }
+        Some(count[target])
+    }
+
+    /// Computes, for each vertex of a DAG, the bitset of vertices reachable
+    /// from it by paths of one or more edges, with vertex v at bit v % 64 of
+    /// word v / 64. Returns None if the graph has a cycle. O(V + VE / 64).
+    fn transitive_closure(&self) -> Option<Vec<Vec<u64>>> {
+        let order = self.topological_sort()?;
+        let mut reach = vec![vec![0u64; self.num_v().div_ceil(64)]; self.num_v()];
+        for &u in order.iter().rev() {
+            for (_, v) in self.adj_list(u) {
+                let reach_v = reach[v].clone();
+                for (word_u, word_v) in reach[u].iter_mut().zip(reach_v) {
+                    *word_u |= word_v;
+                }
+                reach[u][v / 64] |= 1 << (v % 64);
+            }
+        }
+        Some(reach)
+    }
+
+    /// Finds the transitive reduction of a DAG: the graph on the same vertices
+    /// with the fewest edges that has the same reachability. It keeps one copy
+    /// of each edge (u, v) such that v can't be reached from u by a longer
+    /// path. Returns None if the graph has a cycle. O(V + VE / 64).
+    pub fn transitive_reduction(&self) -> Option<Graph> {
+        let reach = self.transitive_closure()?;
+        let mut kept = vec![];
+        for u in 0..self.num_v() {
+            // Collect everything reachable by paths of two or more edges.
+            let mut indirect = vec![0u64; reach[u].len()];
+            for (_, w) in self.adj_list(u) {
+                for (word, &word_w) in indirect.iter_mut().zip(&reach[w]) {
+                    *word |= word_w;
+                }
+            }
+            for (_, v) in self.adj_list(u) {
+                if (indirect[v / 64] >> (v % 64)) & 1 == 0 {
+                    indirect[v / 64] |= 1 << (v % 64);
+                    kept.push((u, v));
+                }
+            }
+        }
+        let mut reduction = Graph::new(self.num_v(), kept.len());
+        for (u, v) in kept {
+            reduction.add_edge(u, v);
+        }
+        Some(reduction)
+    }
+
+    /// Computes a minimum chain cover and a maximum antichain of the partial
+    /// order induced by a DAG, where u precedes v if there's a path from u to
+    /// v. By Dilworth's theorem, both have the same size. Chains are listed in
+    /// order and may skip over vertices, which then belong to other chains.
+    /// Uses bipartite matching on the transitive closure, from whose minimum
+    /// vertex cover the antichain is recovered as in the proof of König's
+    /// theorem. O(V^3) in the worst case.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has a cycle.
+    pub fn dilworth(&self) -> (Vec<Vec<usize>>, Vec<usize>) {
+        let n = self.num_v();
+        let reach = self.transitive_closure().expect("graph has a cycle");
+
+        // Match u on the left to v on the right if v follows u in a chain.
+        let (source, sink) = (2 * n, 2 * n + 1);
+        let mut split = FlowGraph::new(2 * n + 2, 2 * n);
+        for (u, reach_u) in reach.iter().enumerate() {
+            split.add_edge(source, u, 1, 0);
+            split.add_edge(n + u, sink, 1, 0);
+            for v in (0..n).filter(|&v| (reach_u[v / 64] >> (v % 64)) & 1 == 1) {
+                split.add_edge(u, n + v, 1, 0);
+            }
+        }
+        let flow = split.dinic(source, sink).1;
+        let mut succ = vec![None; n];
+        let mut has_pred = vec![false; n];
+        for e in (0..split.graph.num_e()).step_by(2) {
+            let (u, v) = (split.graph.endp[e + 1], split.graph.endp[e]);
+            if u < n && flow[e] > 0 {
+                succ[u] = Some(v - n);
+                has_pred[v - n] = true;
+            }
+        }
+        let chains = (0..n)
+            .filter(|&u| !has_pred[u])
+            .map(|mut u| {
+                let mut chain = vec![u];
+                while let Some(v) = succ[u] {
+                    chain.push(v);
+                    u = v;
+                }
+                chain
+            })
+            .collect();
+
+        // The vertices reachable from the source in the residual graph mark a
+        // minimum vertex cover: the unreached left vertices plus the reached
+        // right vertices. An element is in the antichain iff neither of its
+        // copies is in the cover.
+        let mut reached = vec![false; 2 * n + 2];
+        let mut stack = vec![source];
+        reached[source] = true;
+        while let Some(u) = stack.pop() {
+            for (e, v) in split.graph.adj_list(u) {
+                if !reached[v] && flow[e] < split.cap[e] {
+                    reached[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+        let antichain = (0..n).filter(|&u| reached[u] && !reached[n + u]).collect();
+        (chains, antichain)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::xorshift;
+
+    #[test]
+    fn test_topological_sort() {
+        let mut graph = Graph::new(4, 3);
+        graph.add_edge(2, 0);
+        graph.add_edge(0, 3);
+        graph.add_edge(3, 1);
+        assert_eq!(graph.topological_sort(), Some(vec![2, 0, 3, 1]));
+
+        let mut graph = Graph::new(4, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        let order = graph.topological_sort().unwrap();
+        assert_eq!(order[0], 0);
+        assert_eq!(order[3], 3);
+
+        // Vertex 0 is freed after 2 and 3 become available, but still comes
+        // before them.
+        let mut graph = Graph::new(4, 1);
+        graph.add_edge(1, 0);
+        assert_eq!(graph.topological_sort(), Some(vec![1, 0, 2, 3]));
+
+        let mut graph = Graph::new(3, 3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        assert_eq!(graph.topological_sort(), None);
+    }
+
+    #[test]
+    fn test_topological_sort_lex() {
+        // Placing 0 as early as possible would give [3, 0, 2, 1] instead.
+        let mut graph = Graph::new(4, 2);
+        graph.add_edge(3, 0);
+        graph.add_edge(2, 1);
+        assert_eq!(graph.topological_sort_lex(), Some(vec![2, 1, 3, 0]));
+
+        graph.add_edge(0, 3);
+        assert_eq!(graph.topological_sort_lex(), None);
+    }
+
+    #[test]
+    fn test_has_unique_toposort() {
+        let mut chain = Graph::new(4, 4);
+        chain.add_edge(2, 0);
+        chain.add_edge(0, 3);
+        chain.add_edge(3, 1);
+        chain.add_edge(2, 1);
+        assert!(chain.has_unique_toposort());
+        chain.add_edge(1, 2);
+        assert!(!chain.has_unique_toposort());
+
+        let mut two_sources = Graph::new(3, 2);
+        two_sources.add_edge(0, 2);
+        two_sources.add_edge(1, 2);
+        assert!(!two_sources.has_unique_toposort());
+        assert!(Graph::new(1, 0).has_unique_toposort());
+    }
+
+    #[test]
+    fn test_parallel_schedule() {
+        let mut graph = Graph::new(5, 4);
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(1, 4);
+        assert_eq!(
+            graph.parallel_schedule(),
+            Some(vec![vec![0, 1], vec![4, 2], vec![3]])
+        );
+
+        graph.add_edge(3, 1);
+        assert_eq!(graph.parallel_schedule(), None);
+    }
+
+    #[test]
+    fn test_dag_longest_path() {
+        let mut graph = Graph::new(4, 4);
+        graph.add_edge(0, 3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        assert_eq!(
+            graph.dag_longest_path(&[5, 2, 2, 2]),
+            Some((6, vec![1, 2, 3]))
+        );
+        assert_eq!(graph.dag_longest_path(&[5, 2, -9, 2]), Some((5, vec![0])));
+
+        graph.add_edge(3, 0);
+        assert_eq!(graph.dag_longest_path(&[5, 2, 2, 2, 1]), None);
+
+        let graph = Graph::new(1, 0);
+        assert_eq!(graph.dag_longest_path(&[]), Some((0, vec![])));
+    }
+
+    #[test]
+    fn test_dag_count_paths() {
+        // Monotone lattice paths across a 5x4 grid number binomial(7, 3) = 35.
+        let mut graph = Graph::new(20, 31);
+        for y in 0..4 {
+            for x in 0..5 {
+                if x + 1 < 5 {
+                    graph.add_edge(5 * y + x, 5 * y + x + 1);
+                }
+                if y + 1 < 4 {
+                    graph.add_edge(5 * y + x, 5 * y + x + 5);
+                }
+            }
+        }
+        assert_eq!(graph.dag_count_paths(0, 19, None), Some(35));
+        assert_eq!(graph.dag_count_paths(0, 19, Some(10)), Some(5));
+        assert_eq!(graph.dag_count_paths(0, 0, Some(1)), Some(0));
+        assert_eq!(graph.dag_count_paths(19, 0, None), Some(0));
+
+        graph.add_edge(19, 0);
+        assert_eq!(graph.dag_count_paths(0, 19, None), None);
+    }
+
+    #[test]
+    fn test_topological_sort_linear_extension() {
+        let n = 100_000;
+        let mut graph = Graph::new(n, 2 * n);
+        for u in 1..n {
+            graph.add_edge(u, u - 1);
+            graph.add_edge(u, u / 2);
+        }
+
+        let order = graph.topological_sort().unwrap();
+        let mut pos = vec![0; n];
+        for (i, &u) in order.iter().enumerate() {
+            pos[u] = i;
+        }
+        for u in 0..n {
+            assert!(graph.adj_list(u).all(|(_, v)| pos[u] < pos[v]));
+        }
+
+        let mut graph = Graph::new(2, 2);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 0);
+        assert_eq!(graph.topological_sort(), None);
+    }
+
+    #[test]
+    fn test_transitive_reduction() {
+        let mut graph = Graph::new(4, 6);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(2, 3);
+        graph.add_edge(0, 3);
+
+        let reduction = graph.transitive_reduction().unwrap();
+        let edges = (0..4)
+            .flat_map(|u| reduction.adj_list(u).map(move |(_, v)| (u, v)))
+            .collect::<Vec<_>>();
+        assert_eq!(edges, vec![(0, 1), (1, 2), (2, 3)]);
+
+        graph.add_edge(3, 0);
+        assert!(graph.transitive_reduction().is_none());
+    }
+
+    #[test]
+    fn test_dilworth() {
+        let mut rand = xorshift(0x5851_f42d_4c95_7f2d);
+        for trial in 0..30 {
+            let n = 1 + trial % 50;
+            let mut graph = Graph::new(n, n * n);
+            for u in 0..n {
+                for v in u + 1..n {
+                    if rand().is_multiple_of(8) {
+                        graph.add_edge(u, v);
+                    }
+                }
+            }
+            let (chains, antichain) = graph.dilworth();
+            let precedes = |u: usize, v: usize| {
+                let (dist, _) = graph.bfs(u);
+                u != v && dist[v].is_some()
+            };
+
+            let mut covered = chains.concat();
+            covered.sort_unstable();
+            assert_eq!(covered, (0..n).collect::<Vec<_>>());
+            for chain in &chains {
+                assert!(chain.windows(2).all(|w| precedes(w[0], w[1])));
+            }
+            for &u in &antichain {
+                assert!(antichain.iter().all(|&v| !precedes(u, v)));
+            }
+            assert_eq!(chains.len(), antichain.len());
+        }
+
+        // A diamond is covered by the chains 0 -> 1 -> 3 and 2.
+        let mut graph = Graph::new(4, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        let (chains, antichain) = graph.dilworth();
+        assert_eq!(chains.len(), 2);
+        assert_eq!(antichain, vec![1, 2]);
+    }
+}
@@ -1,9 +1,25 @@
+//! Depth-first search traversals and edge classification.
 use super::Graph;
 use crate::graph::AdjListIterator;
 use bit_vec::BitVec;
 
+/// The role of an edge (u, v) in a depth-first search forest.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// The search discovered v through this edge.
+    Tree,
+    /// v is an ancestor of u, or u itself.
+    Back,
+    /// v is a descendant of u, discovered through another edge.
+    Forward,
+    /// v is neither an ancestor nor a descendant of u.
+    Cross,
+    /// The search never reached u.
+    Unvisited,
+}
+
 impl Graph {
-    pub fn dfs(&self, v: usize) -> DfsIterator {
+    pub fn dfs(&self, v: usize) -> DfsIterator<'_> {
         // Create a stack for DFS
         let mut stack: Vec<usize> = Vec::new();
 
@@ -20,6 +36,67 @@ impl Graph {
             adj_iters,
         }
     }
+
+    /// Performs a depth-first search from src, returning the times at which
+    /// each vertex is discovered and finished, on a single shared clock that
+    /// starts at 0. Vertices unreachable from src keep None. The explicit
+    /// stack avoids overflow on deep graphs.
+    pub fn dfs_times(&self, src: usize) -> (Vec<Option<u32>>, Vec<Option<u32>>) {
+        let mut discover = vec![None; self.num_v()];
+        let mut finish = vec![None; self.num_v()];
+        let mut time = 0;
+
+        discover[src] = Some(time);
+        let mut stack = vec![(src, self.adj_list(src))];
+        while let Some((u, adj)) = stack.last_mut() {
+            let u = *u;
+            time += 1;
+            if let Some((_, v)) = adj.find(|&(_, v)| discover[v].is_none()) {
+                discover[v] = Some(time);
+                stack.push((v, self.adj_list(v)));
+            } else {
+                finish[u] = Some(time);
+                stack.pop();
+            }
+        }
+        (discover, finish)
+    }
+
+    /// Classifies every edge of a directed graph relative to a depth-first
+    /// search from root, which traverses adjacency lists in the same order as
+    /// dfs_times(). An explored edge (u, v) is a back edge iff v hadn't yet
+    /// finished, and otherwise a forward or cross edge according to whether
+    /// v was discovered after u. O(V + E).
+    pub fn classify_edges(&self, root: usize) -> Vec<EdgeKind> {
+        let mut kind = vec![EdgeKind::Unvisited; self.num_e()];
+        let mut discover = vec![None; self.num_v()];
+        let mut finished = vec![false; self.num_v()];
+        let mut time = 0;
+
+        discover[root] = Some(time);
+        let mut stack = vec![(root, self.adj_list(root))];
+        while let Some((u, adj)) = stack.last_mut() {
+            let u = *u;
+            if let Some((e, v)) = adj.next() {
+                kind[e] = if discover[v].is_none() {
+                    time += 1;
+                    discover[v] = Some(time);
+                    stack.push((v, self.adj_list(v)));
+                    EdgeKind::Tree
+                } else if !finished[v] {
+                    EdgeKind::Back
+                } else if discover[v] > discover[u] {
+                    EdgeKind::Forward
+                } else {
+                    EdgeKind::Cross
+                };
+            } else {
+                finished[u] = true;
+                stack.pop();
+            }
+        }
+        kind
+    }
 }
 pub struct DfsIterator<'a> {
     //is vertex visited
@@ -94,6 +171,40 @@ mod test {
         assert_eq!(dfs_search, vec![0, 3, 4, 2, 1]);
     }
 
+    #[test]
+    fn test_dfs_times() {
+        let mut graph = Graph::new(5, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 3);
+        graph.add_edge(4, 0);
+
+        // Adjacency lists are traversed in reverse order of insertion.
+        let (discover, finish) = graph.dfs_times(0);
+        assert_eq!(discover, vec![Some(0), Some(3), Some(1), Some(4), None]);
+        assert_eq!(finish, vec![Some(7), Some(6), Some(2), Some(5), None]);
+    }
+
+    #[test]
+    fn test_classify_edges() {
+        let mut graph = Graph::new(5, 8);
+        graph.add_edge(0, 2);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 0);
+        graph.add_edge(2, 2);
+        graph.add_edge(3, 1);
+        graph.add_edge(0, 4);
+        graph.add_edge(4, 2);
+
+        // The search visits 0, 4, 2, then 1.
+        use EdgeKind::*;
+        assert_eq!(
+            graph.classify_edges(0),
+            vec![Forward, Tree, Cross, Back, Back, Unvisited, Tree, Tree]
+        );
+    }
+
     #[test]
     fn test_dfs_space_complexity() {
         let num_v = 20;
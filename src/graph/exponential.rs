@@ -0,0 +1,263 @@
+//! Exact exponential-time algorithms for hard problems on small graphs.
+use super::{DisjointSets, Graph};
+
+impl Graph {
+    /// Returns, for each vertex, the bitmask of vertices with an edge into it.
+    fn pred_masks(&self) -> Vec<u32> {
+        assert!(self.num_v() < 32, "too many vertices for bitmask DP");
+        let mut pred = vec![0; self.num_v()];
+        for u in 0..self.num_v() {
+            for (_, v) in self.adj_list(u) {
+                pred[v] |= 1 << u;
+            }
+        }
+        pred
+    }
+
+    /// Determines whether some path visits every vertex exactly once, by
+    /// dynamic programming over subsets: reach[mask] is the set of vertices at
+    /// which a path covering exactly mask can end. O(2^V * V) time and 2^V
+    /// words of memory, so V should be at most about 25.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are 32 or more vertices.
+    pub fn has_hamiltonian_path(&self) -> bool {
+        let pred = self.pred_masks();
+        let n = self.num_v();
+        let mut reach = vec![0u32; 1 << n];
+        for v in 0..n {
+            reach[1 << v] = 1 << v;
+        }
+        for mask in 1..1 << n {
+            if reach[mask] == 0 {
+                continue;
+            }
+            for (w, &pred_w) in pred.iter().enumerate() {
+                if mask & (1 << w) == 0 && reach[mask] & pred_w != 0 {
+                    reach[mask | (1 << w)] |= 1 << w;
+                }
+            }
+        }
+        n == 0 || reach[(1 << n) - 1] != 0
+    }
+
+    /// Computes the number of vertices left unmatched by a maximum matching of
+    /// an undirected graph, by the Tutte-Berge formula: the maximum, over all
+    /// vertex subsets S, of the number of odd components of G - S minus |S|.
+    /// Enumerating the subsets takes O(2^V * (V + E)) time, so V should be at
+    /// most about 20; the optimal S certifies that no larger matching exists.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are 32 or more vertices.
+    pub fn matching_deficiency(&self) -> usize {
+        assert!(
+            self.num_v() < 32,
+            "too many vertices for subset enumeration"
+        );
+        let n = self.num_v();
+        let tail = self.tails();
+        (0..1u32 << n)
+            .map(|removed| {
+                let mut components = DisjointSets::new(n);
+                for (e, &u) in tail.iter().enumerate() {
+                    let v = self.endp[e];
+                    if removed & (1 << u) == 0 && removed & (1 << v) == 0 {
+                        components.merge(u, v);
+                    }
+                }
+                let num_odd = (0..n)
+                    .filter(|&u| {
+                        removed & (1 << u) == 0
+                            && components.find(u) == u
+                            && components.size(u) % 2 == 1
+                    })
+                    .count();
+                num_odd.saturating_sub(removed.count_ones() as usize)
+            })
+            .max()
+            .unwrap()
+    }
+
+    /// Finds the minimum total weight of a set of edges whose removal leaves
+    /// the graph acyclic. The problem is NP-hard, so this solves its
+    /// complement exactly: the remaining edges point forward in some vertex
+    /// order, and the best order is built by dynamic programming over the
+    /// subset of vertices placed first. Weights must be nonnegative, and
+    /// self-loops are always removed. O(2^V * E) time and 2^V words of
+    /// memory, so V should be at most about 20.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are 32 or more vertices.
+    pub fn min_feedback_edge_set(&self, weights: &[i64]) -> i64 {
+        assert_eq!(self.num_e(), weights.len());
+        assert!(self.num_v() < 32, "too many vertices for bitmask DP");
+        let n = self.num_v();
+        let mut in_edges = vec![vec![]; n];
+        for (e, u) in self.tails().into_iter().enumerate() {
+            in_edges[self.endp[e]].push((u, weights[e]));
+        }
+        // kept[mask] is the maximum weight of forward edges among the vertices
+        // in mask, over all orders of them.
+        let mut kept = vec![i64::MIN; 1 << n];
+        kept[0] = 0;
+        for mask in 0..1usize << n {
+            for (v, in_v) in in_edges.iter().enumerate() {
+                if mask & (1 << v) == 0 {
+                    let gain = in_v
+                        .iter()
+                        .filter(|&&(u, _)| mask & (1 << u) != 0)
+                        .map(|&(_, w)| w)
+                        .sum::<i64>();
+                    let next = mask | (1 << v);
+                    kept[next] = kept[next].max(kept[mask] + gain);
+                }
+            }
+        }
+        weights.iter().sum::<i64>() - kept[(1 << n) - 1]
+    }
+}
+
+/// Held-Karp algorithm: finds a minimum-cost cycle visiting every vertex of a
+/// complete graph exactly once, given as the pair of its cost and visiting
+/// order starting from vertex 0. dist[u][v] is the cost of going from u to v;
+/// it need not be symmetric. O(2^V * V^2) time and O(2^V * V) memory, so V
+/// should be at most about 15.
+///
+/// # Panics
+///
+/// Panics if dist isn't a square matrix or has 32 or more rows.
+pub fn tsp_held_karp(dist: &[Vec<i64>]) -> (i64, Vec<usize>) {
+    let n = dist.len();
+    assert!(n < 32, "too many vertices for bitmask DP");
+    assert!(dist.iter().all(|row| row.len() == n));
+    if n <= 1 {
+        return (0, (0..n).collect());
+    }
+
+    // cost[mask][v] is the minimum cost of a path from 0 that visits exactly
+    // the vertices in mask, which must include 0 and v, ending at v.
+    let mut cost = vec![vec![i64::MAX; n]; 1 << n];
+    let mut pred = vec![vec![0; n]; 1 << n];
+    cost[1][0] = 0;
+    for mask in (1..1 << n).step_by(2) {
+        for u in 0..n {
+            if cost[mask][u] == i64::MAX {
+                continue;
+            }
+            for v in 0..n {
+                let next = mask | (1 << v);
+                if next != mask && cost[next][v] > cost[mask][u] + dist[u][v] {
+                    cost[next][v] = cost[mask][u] + dist[u][v];
+                    pred[next][v] = u;
+                }
+            }
+        }
+    }
+
+    let full = (1 << n) - 1;
+    let last = (1..n).min_by_key(|&v| cost[full][v] + dist[v][0]).unwrap();
+    let mut order = vec![last];
+    let (mut mask, mut v) = (full, last);
+    while v != 0 {
+        let u = pred[mask][v];
+        mask ^= 1 << v;
+        v = u;
+        order.push(v);
+    }
+    order.reverse();
+    (cost[full][last] + dist[last][0], order)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hamiltonian_path() {
+        let mut path = Graph::new(5, 8);
+        path.add_undirected_edge(3, 1);
+        path.add_undirected_edge(1, 4);
+        path.add_undirected_edge(4, 0);
+        path.add_undirected_edge(0, 2);
+        assert!(path.has_hamiltonian_path());
+
+        let mut star = Graph::new(5, 8);
+        for leaf in 1..5 {
+            star.add_undirected_edge(0, leaf);
+        }
+        assert!(!star.has_hamiltonian_path());
+
+        let mut directed = Graph::new(3, 2);
+        directed.add_edge(0, 1);
+        directed.add_edge(2, 1);
+        assert!(!directed.has_hamiltonian_path());
+        directed.add_edge(1, 2);
+        assert!(directed.has_hamiltonian_path());
+    }
+
+    #[test]
+    fn test_min_feedback_edge_set() {
+        let mut triangle = Graph::new(3, 3);
+        triangle.add_edge(0, 1);
+        triangle.add_edge(1, 2);
+        triangle.add_edge(2, 0);
+        assert_eq!(triangle.min_feedback_edge_set(&[5, 2, 7]), 2);
+
+        // The 2-cycles through vertex 1 and the self-loop at 2 each lose their
+        // lightest edge: 3 + 1 + 2.
+        let mut bowtie = Graph::new(3, 5);
+        bowtie.add_edge(0, 1);
+        bowtie.add_edge(1, 0);
+        bowtie.add_edge(1, 2);
+        bowtie.add_edge(2, 1);
+        bowtie.add_edge(2, 2);
+        assert_eq!(bowtie.min_feedback_edge_set(&[4, 3, 1, 6, 2]), 6);
+
+        let mut dag = Graph::new(3, 2);
+        dag.add_edge(0, 1);
+        dag.add_edge(2, 1);
+        assert_eq!(dag.min_feedback_edge_set(&[9, 9]), 0);
+    }
+
+    #[test]
+    fn test_matching_deficiency() {
+        let mut pentagon = Graph::new(5, 10);
+        for u in 0..5 {
+            pentagon.add_undirected_edge(u, (u + 1) % 5);
+        }
+        assert_eq!(pentagon.matching_deficiency(), 1);
+
+        // Removing the center of a star with 4 leaves isolates them all.
+        let mut star = Graph::new(5, 8);
+        for leaf in 1..5 {
+            star.add_undirected_edge(0, leaf);
+        }
+        assert_eq!(star.matching_deficiency(), 3);
+
+        let mut square = Graph::new(4, 8);
+        for u in 0..4 {
+            square.add_undirected_edge(u, (u + 1) % 4);
+        }
+        assert_eq!(square.matching_deficiency(), 0);
+        assert_eq!(Graph::new(0, 0).matching_deficiency(), 0);
+    }
+
+    #[test]
+    fn test_tsp_held_karp() {
+        let dist = vec![
+            vec![0, 10, 15, 20],
+            vec![10, 0, 35, 25],
+            vec![15, 35, 0, 30],
+            vec![20, 25, 30, 0],
+        ];
+        let (cost, order) = tsp_held_karp(&dist);
+        assert_eq!(cost, 80);
+        assert!(order == vec![0, 1, 3, 2] || order == vec![0, 2, 3, 1]);
+
+        assert_eq!(tsp_held_karp(&[vec![0]]), (0, vec![0]));
+        assert_eq!(tsp_held_karp(&[vec![0, 3], vec![4, 0]]), (7, vec![0, 1]));
+    }
+}
@@ -24,6 +24,18 @@ impl FlowGraph {
         }
     }
 
+    /// Builds a flow network with the same vertices as graph, in which each of
+    /// its edges e becomes the edge 2e with capacity capacities[e] and zero
+    /// cost, paired with its reverse edge 2e+1.
+    pub fn from_graph(graph: &Graph, capacities: &[i64]) -> Self {
+        assert_eq!(graph.num_e(), capacities.len());
+        let mut flow_graph = Self::new(graph.num_v(), graph.num_e());
+        for (e, u) in graph.tails().into_iter().enumerate() {
+            flow_graph.add_edge(u, graph.endp[e], capacities[e], 0);
+        }
+        flow_graph
+    }
+
     /// Adds an edge with specified capacity and cost. The reverse edge is also
     /// added for residual graph computation, but has zero capacity.
     pub fn add_edge(&mut self, u: usize, v: usize, cap: i64, cost: i64) {
@@ -31,7 +43,33 @@ impl FlowGraph {
         self.cap.push(0);
         self.cost.push(cost);
         self.cost.push(-cost);
-        self.graph.add_undirected_edge(u, v);
+        self.graph.add_residual_pair(u, v);
+    }
+
+    /// Limits the total flow that can pass through vertex v to cap, by
+    /// splitting v into an in-vertex, which keeps the id v and the edges into
+    /// v, and a new out-vertex, which takes over the edges out of v. The two
+    /// are joined by an edge of the given capacity and zero cost. Returns the
+    /// id of the out-vertex, which must be used as the tail of any edges out
+    /// of v that are added later. As a flow source, v should be replaced by
+    /// its out-vertex unless its own capacity is meant to apply.
+    pub fn add_vertex_capacity(&mut self, v: usize, cap: i64) -> usize {
+        let v_out = self.graph.num_v();
+        self.graph.first.push(None);
+        let edges = self.graph.adj_list(v).map(|(e, _)| e).collect::<Vec<_>>();
+        self.graph.first[v] = None;
+        // Relink in reverse to preserve the adjacency lists' order. Even ids
+        // are edges out of v, while odd ids are residuals of edges into v.
+        for &e in edges.iter().rev() {
+            let u = if e % 2 == 0 { v_out } else { v };
+            self.graph.next[e] = self.graph.first[u];
+            self.graph.first[u] = Some(e);
+            if e % 2 == 0 {
+                self.graph.endp[e ^ 1] = v_out;
+            }
+        }
+        self.add_edge(v, v_out, cap, 0);
+        v_out
     }
 
     /// Dinic's algorithm to find the maximum flow from s to t where s != t.
@@ -108,6 +146,48 @@ impl FlowGraph {
         df
     }
 
+    /// Builds the residual graph of a flow, such as the one returned by
+    /// dinic(): it has the edges with positive residual capacity, added in
+    /// increasing order of id.
+    pub fn residual_graph(&self, flow: &[i64]) -> Graph {
+        assert_eq!(self.graph.num_e(), flow.len());
+        let residual_edges = (0..flow.len())
+            .filter(|&e| flow[e] < self.cap[e])
+            .collect::<Vec<_>>();
+        let mut residual = Graph::new(self.graph.num_v(), residual_edges.len());
+        for e in residual_edges {
+            residual.add_edge(self.graph.endp[e ^ 1], self.graph.endp[e]);
+        }
+        residual
+    }
+
+    /// Checks that flow is a maximum flow from s to t of value claimed: each
+    /// edge's flow is the negation of its reverse's and within its capacity,
+    /// flow is conserved at every vertex other than s and t, the net outflow
+    /// from s is claimed, and the vertices reachable from s in the residual
+    /// graph form a cut of capacity claimed that excludes t. O(V + E).
+    pub fn verify_max_flow(&self, flow: &[i64], s: usize, t: usize, claimed: i64) -> bool {
+        if flow.len() != self.graph.num_e()
+            || (0..flow.len()).any(|e| flow[e] != -flow[e ^ 1] || flow[e] > self.cap[e])
+        {
+            return false;
+        }
+        let net_outflow = |u: usize| self.graph.adj_list(u).map(|(e, _)| flow[e]).sum::<i64>();
+        if (0..self.graph.num_v()).any(|u| u != s && u != t && net_outflow(u) != 0)
+            || net_outflow(s) != claimed
+        {
+            return false;
+        }
+
+        let dist = self.dinic_search(s, flow);
+        let cut_capacity = self
+            .min_cut(&dist)
+            .into_iter()
+            .map(|e| self.cap[e])
+            .sum::<i64>();
+        dist[t] == Self::INF && cut_capacity == claimed
+    }
+
     /// After running maximum flow, use this to recover the dual minimum cut.
     pub fn min_cut(&self, dist: &[i64]) -> Vec<usize> {
         (0..self.graph.num_e())
@@ -143,7 +223,7 @@ impl FlowGraph {
         let (mut min_cost, mut max_flow) = (0, 0);
         loop {
             let par = self.mcf_search(s, &flow, &mut pot);
-            if par[t] == None {
+            if par[t].is_none() {
                 break;
             }
             let (dc, df) = self.mcf_augment(t, &par, &mut flow);
@@ -196,6 +276,122 @@ impl FlowGraph {
     }
 }
 
+impl Graph {
+    /// Determines whether mate describes a matching: mate[u] == Some(v) iff
+    /// mate[v] == Some(u), in which case u != v and some edge joins u and v.
+    pub fn is_valid_matching(&self, mate: &[Option<usize>]) -> bool {
+        mate.len() == self.num_v()
+            && (0..self.num_v()).all(|u| match mate[u] {
+                Some(v) => {
+                    u != v
+                        && mate[v] == Some(u)
+                        && (self.adj_list(u).any(|(_, w)| w == v)
+                            || self.adj_list(v).any(|(_, w)| w == u))
+                }
+                None => true,
+            })
+    }
+
+    /// Determines whether mate describes a maximum matching of a bipartite
+    /// graph whose left side consists of vertices 0..left_count, and whose
+    /// edges go from left to right (possibly also in reverse). By Berge's
+    /// lemma, it's maximum iff no alternating path joins two unmatched
+    /// vertices. O(V + E).
+    pub fn is_maximum_matching(&self, mate: &[Option<usize>], left_count: usize) -> bool {
+        if !self.is_valid_matching(mate) {
+            return false;
+        }
+        let mut visited = vec![false; self.num_v()];
+        let mut stack = (0..left_count)
+            .filter(|&u| mate[u].is_none())
+            .collect::<Vec<_>>();
+        for &u in &stack {
+            visited[u] = true;
+        }
+        while let Some(u) = stack.pop() {
+            for (_, v) in self.adj_list(u).filter(|&(_, v)| v >= left_count) {
+                match mate[v] {
+                    None => return false,
+                    Some(w) if !visited[w] => {
+                        visited[w] = true;
+                        stack.push(w);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        true
+    }
+}
+
+/// Finds a maximum matching in a bipartite graph, given its number of left
+/// vertices and its edges as (left, right) pairs, with each side numbered from
+/// 0. Runs dinic() on the unit-capacity network from a source through the
+/// edges to a sink, which takes O(sqrt(V)E) as in Hopcroft-Karp. Returns the
+/// right partner of each left vertex, if any.
+pub fn bipartite_matching_via_flow(
+    left_count: usize,
+    edges: &[(usize, usize)],
+) -> Vec<Option<usize>> {
+    let right_count = edges.iter().map(|&(_, v)| v + 1).max().unwrap_or(0);
+    let (source, sink) = (left_count + right_count, left_count + right_count + 1);
+    let mut graph = FlowGraph::new(sink + 1, left_count + right_count + edges.len());
+    for &(u, v) in edges {
+        graph.add_edge(u, left_count + v, 1, 0);
+    }
+    for u in 0..left_count {
+        graph.add_edge(source, u, 1, 0);
+    }
+    for v in 0..right_count {
+        graph.add_edge(left_count + v, sink, 1, 0);
+    }
+
+    let flow = graph.dinic(source, sink).1;
+    let mut mate = vec![None; left_count];
+    for (i, &(u, v)) in edges.iter().enumerate() {
+        if flow[2 * i] > 0 {
+            mate[u] = Some(v);
+        }
+    }
+    mate
+}
+
+/// Segments a width x height grid of cells, numbered in row-major order, into
+/// foreground and background by minimizing an energy: labeling cell i as
+/// background costs fg[i], labeling it as foreground costs bg[i], and each
+/// pair of adjacent cells with different labels costs smoothness. Returns the
+/// minimum energy and whether each cell is labeled foreground. Solved by a
+/// minimum cut between a foreground source and a background sink.
+pub fn grid_min_cut(
+    width: usize,
+    height: usize,
+    fg: &[i64],
+    bg: &[i64],
+    smoothness: i64,
+) -> (i64, Vec<bool>) {
+    let n = width * height;
+    assert_eq!(fg.len(), n);
+    assert_eq!(bg.len(), n);
+    let (source, sink) = (n, n + 1);
+    let mut graph = FlowGraph::new(n + 2, 6 * n);
+    for i in 0..n {
+        graph.add_edge(source, i, fg[i], 0);
+        graph.add_edge(i, sink, bg[i], 0);
+        if i % width + 1 < width {
+            graph.add_edge(i, i + 1, smoothness, 0);
+            graph.add_edge(i + 1, i, smoothness, 0);
+        }
+        if i + width < n {
+            graph.add_edge(i, i + width, smoothness, 0);
+            graph.add_edge(i + width, i, smoothness, 0);
+        }
+    }
+    let (energy, flow) = graph.dinic(source, sink);
+    let dist = graph.dinic_search(source, &flow);
+    let labels = dist[..n].iter().map(|&d| d < FlowGraph::INF).collect();
+    (energy, labels)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -210,6 +406,125 @@ mod test {
         assert_eq!(flow, 3);
     }
 
+    #[test]
+    fn test_flow_edges_directed() {
+        let mut graph = FlowGraph::new(3, 2);
+        graph.add_edge(0, 1, 4, 1);
+        graph.add_edge(1, 2, 3, 1);
+        graph.add_vertex_capacity(1, 2);
+
+        for e in 0..graph.graph.num_e() {
+            assert!(!graph.graph.is_bidirectional(e));
+            assert_eq!(graph.graph.endp[e ^ 1], graph.graph.tails()[e]);
+        }
+    }
+
+    #[test]
+    fn test_dinic() {
+        // The example network from CLRS, whose maximum flow is 23.
+        let mut graph = FlowGraph::new(6, 9);
+        graph.add_edge(0, 1, 16, 0);
+        graph.add_edge(0, 2, 13, 0);
+        graph.add_edge(1, 3, 12, 0);
+        graph.add_edge(2, 1, 4, 0);
+        graph.add_edge(2, 4, 14, 0);
+        graph.add_edge(3, 2, 9, 0);
+        graph.add_edge(3, 5, 20, 0);
+        graph.add_edge(4, 3, 7, 0);
+        graph.add_edge(4, 5, 4, 0);
+
+        let (flow_amt, flow) = graph.dinic(0, 5);
+        assert_eq!(flow_amt, 23);
+        for e in 0..graph.graph.num_e() {
+            assert!(flow[e] <= graph.cap[e]);
+            assert_eq!(flow[e], -flow[e ^ 1]);
+        }
+
+        let mut graph = FlowGraph::new(4, 2);
+        graph.add_edge(0, 1, 5, 0);
+        graph.add_edge(2, 3, 5, 0);
+        assert_eq!(graph.dinic(0, 3).0, 0);
+    }
+
+    #[test]
+    fn test_from_graph() {
+        let mut graph = Graph::new(4, 5);
+        let mut direct = FlowGraph::new(4, 5);
+        for &(u, v, cap) in &[(0, 1, 3), (0, 2, 2), (1, 2, 5), (1, 3, 2), (2, 3, 3)] {
+            graph.add_edge(u, v);
+            direct.add_edge(u, v, cap, 0);
+        }
+        let flow_graph = FlowGraph::from_graph(&graph, &[3, 2, 5, 2, 3]);
+        assert_eq!(flow_graph.graph.endp, direct.graph.endp);
+        assert_eq!(flow_graph.cap, direct.cap);
+        assert_eq!(flow_graph.dinic(0, 3).0, 5);
+        assert_eq!(flow_graph.dinic(0, 3), direct.dinic(0, 3));
+    }
+
+    #[test]
+    fn test_residual_graph() {
+        let mut graph = FlowGraph::new(4, 5);
+        graph.add_edge(0, 1, 3, 0);
+        graph.add_edge(0, 2, 2, 0);
+        graph.add_edge(1, 2, 5, 0);
+        graph.add_edge(1, 3, 2, 0);
+        graph.add_edge(2, 3, 3, 0);
+
+        let residual = graph.residual_graph(&[0; 10]);
+        assert_eq!(residual.num_e(), 5);
+        assert_eq!(residual.bfs(0).0[3], Some(2));
+
+        let (_, flow) = graph.dinic(0, 3);
+        let residual = graph.residual_graph(&flow);
+        let dist = residual.bfs(0).0;
+        assert_eq!(dist[3], None);
+        assert!(residual.bfs(3).0[0].is_some());
+    }
+
+    #[test]
+    fn test_verify_max_flow() {
+        let mut graph = FlowGraph::new(4, 5);
+        graph.add_edge(0, 1, 3, 0);
+        graph.add_edge(0, 2, 2, 0);
+        graph.add_edge(1, 2, 5, 0);
+        graph.add_edge(1, 3, 2, 0);
+        graph.add_edge(2, 3, 3, 0);
+
+        let (flow_amt, mut flow) = graph.dinic(0, 3);
+        assert!(graph.verify_max_flow(&flow, 0, 3, flow_amt));
+        assert!(!graph.verify_max_flow(&flow, 0, 3, flow_amt - 1));
+
+        // Sending one less unit along 0 -> 2 -> 3 is a valid, smaller flow.
+        flow[2] -= 1;
+        flow[3] += 1;
+        flow[8] -= 1;
+        flow[9] += 1;
+        assert!(!graph.verify_max_flow(&flow, 0, 3, flow_amt - 1));
+
+        // Dropping only 2 -> 3 breaks conservation at 2.
+        flow[2] += 1;
+        flow[3] -= 1;
+        assert!(!graph.verify_max_flow(&flow, 0, 3, flow_amt));
+    }
+
+    #[test]
+    fn test_vertex_capacity() {
+        let mut graph = FlowGraph::new(4, 5);
+        graph.add_edge(0, 1, 10, 0);
+        graph.add_edge(0, 2, 10, 0);
+        graph.add_edge(1, 3, 10, 0);
+        graph.add_edge(2, 3, 4, 0);
+        assert_eq!(graph.dinic(0, 3).0, 14);
+
+        // No edge has capacity below 4, but only 3 units can pass through 1.
+        let out = graph.add_vertex_capacity(1, 3);
+        assert_eq!(out, 4);
+        assert_eq!(graph.dinic(0, 3).0, 7);
+        graph.add_edge(out, 2, 10, 0);
+        assert_eq!(graph.dinic(0, 3).0, 7);
+        assert_eq!(graph.dinic(out, 3).0, 14);
+    }
+
     #[test]
     fn test_min_cost_flow() {
         let mut graph = FlowGraph::new(4, 4);
@@ -224,6 +539,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::identity_op)]
     fn test_max_matching() {
         let mut graph = FlowGraph::new(14, 4);
 
@@ -272,4 +588,78 @@ mod test {
             vec![(1, 8), (3, 7), (4, 9), (5, 10), (6, 12)]
         );
     }
+
+    #[test]
+    fn test_matching_checks() {
+        // Left vertices 0, 1, 2 and right vertices 3, 4, 5.
+        let mut graph = Graph::new(6, 5);
+        graph.add_edge(0, 3);
+        graph.add_edge(0, 4);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 4);
+        graph.add_edge(2, 5);
+
+        let maximum = [Some(4), Some(3), Some(5), Some(1), Some(0), Some(2)];
+        assert!(graph.is_valid_matching(&maximum));
+        assert!(graph.is_maximum_matching(&maximum, 3));
+
+        // 1 -> 3 -> 0 -> 4 -> 2 -> 5 is an augmenting path.
+        let partial = [Some(3), None, Some(4), Some(0), Some(2), None];
+        assert!(graph.is_valid_matching(&partial));
+        assert!(!graph.is_maximum_matching(&partial, 3));
+
+        let asymmetric = [Some(3), None, None, None, None, None];
+        assert!(!graph.is_valid_matching(&asymmetric));
+        let non_edge = [Some(5), None, None, None, None, Some(0)];
+        assert!(!graph.is_valid_matching(&non_edge));
+    }
+
+    #[test]
+    fn test_bipartite_matching_via_flow() {
+        let edges = [
+            (0, 1),
+            (0, 2),
+            (2, 0),
+            (2, 3),
+            (3, 2),
+            (4, 2),
+            (4, 3),
+            (5, 5),
+        ];
+        let mate = bipartite_matching_via_flow(6, &edges);
+        assert_eq!(mate.iter().flatten().count(), 5);
+
+        // Check maximality against the same instance built as a Graph, with
+        // left vertices 0..6 and right vertices 6..12.
+        let mut graph = Graph::new(12, edges.len());
+        for &(u, v) in &edges {
+            graph.add_edge(u, 6 + v);
+        }
+        let mut full_mate = vec![None; 12];
+        for (u, &v) in mate.iter().enumerate() {
+            if let Some(v) = v {
+                full_mate[u] = Some(6 + v);
+                full_mate[6 + v] = Some(u);
+            }
+        }
+        assert!(graph.is_maximum_matching(&full_mate, 6));
+        assert_eq!(bipartite_matching_via_flow(2, &[]), vec![None, None]);
+    }
+
+    #[test]
+    fn test_grid_min_cut() {
+        // The top row prefers the foreground, and the bottom row the background.
+        let (energy, labels) = grid_min_cut(2, 2, &[9, 8, 0, 1], &[0, 1, 9, 7], 1);
+        assert_eq!(energy, 4);
+        assert_eq!(labels, vec![true, true, false, false]);
+
+        // Smoothing relabels a weakly background cell between two foreground
+        // cells, unless smoothness is cheap.
+        let (fg, bg) = ([10, 0, 10], [0, 3, 0]);
+        assert_eq!(grid_min_cut(3, 1, &fg, &bg, 5), (3, vec![true; 3]));
+        assert_eq!(
+            grid_min_cut(3, 1, &fg, &bg, 1),
+            (2, vec![true, false, true])
+        );
+    }
 }
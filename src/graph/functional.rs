@@ -0,0 +1,112 @@
+//! Functional graphs, in which every vertex has exactly one successor.
+
+/// Binary lifting table answering "where does v go after k steps?" in
+/// O(log k) time. Uses O(n * max_power) memory.
+pub struct SuccessorTable {
+    /// jump[j][v] is the 2^j-th successor of v.
+    jump: Vec<Vec<usize>>,
+}
+
+impl SuccessorTable {
+    /// Precomputes the table from each vertex's successor, supporting queries
+    /// with step counts below 2^max_power.
+    ///
+    /// # Panics
+    ///
+    /// Panics if max_power exceeds 64.
+    pub fn new(succ: &[usize], max_power: u32) -> Self {
+        assert!(max_power <= 64, "step counts are at most 64 bits");
+        let mut jump: Vec<Vec<usize>> = Vec::with_capacity(max_power as usize);
+        if max_power > 0 {
+            jump.push(succ.to_vec());
+        }
+        while jump.len() < max_power as usize {
+            let prev = &jump[jump.len() - 1];
+            let next = prev.iter().map(|&v| prev[v]).collect();
+            jump.push(next);
+        }
+        Self { jump }
+    }
+
+    // Panics if k is too large for the table.
+    fn check_steps(&self, k: u64) {
+        assert!(
+            self.jump.len() == 64 || k >> self.jump.len() == 0,
+            "too many steps for this table"
+        );
+    }
+
+    /// Finds the k-th successor of v.
+    pub fn kth(&self, mut v: usize, k: u64) -> usize {
+        self.check_steps(k);
+        for (j, jump) in self.jump.iter().enumerate() {
+            if (k >> j) & 1 == 1 {
+                v = jump[v];
+            }
+        }
+        v
+    }
+
+    /// Finds the smallest k <= limit such that u and v have the same k-th
+    /// successor, or None if there is none. Once the two walks meet, they stay
+    /// together forever, so the meeting time can be found by binary lifting.
+    pub fn first_meeting(&self, mut u: usize, mut v: usize, limit: u64) -> Option<u64> {
+        self.check_steps(limit);
+        if u == v {
+            return Some(0);
+        }
+        // Find the largest k <= limit at which the walks are still apart.
+        let mut k = 0;
+        for (j, jump) in self.jump.iter().enumerate().rev() {
+            if limit - k >= 1 << j && jump[u] != jump[v] {
+                u = jump[u];
+                v = jump[v];
+                k += 1 << j;
+            }
+        }
+        if k < limit {
+            Some(k + 1)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cycle() {
+        let table = SuccessorTable::new(&[1, 2, 3, 4, 0], 60);
+        for v in 0..5 {
+            assert_eq!(table.kth(v, 0), v);
+            assert_eq!(table.kth(v, 7), (v + 2) % 5);
+            assert_eq!(table.kth(v, 1_000_000_000_000_000_003), (v + 3) % 5);
+        }
+        assert_eq!(table.first_meeting(0, 1, 1_000_000), None);
+    }
+
+    #[test]
+    fn test_rho() {
+        // Tails 0 -> 1 and 5 -> 1, leading into the cycle 2 -> 3 -> 4 -> 2.
+        let table = SuccessorTable::new(&[1, 2, 3, 4, 2, 1], 60);
+        assert_eq!(table.kth(0, 0), 0);
+        assert_eq!(table.kth(0, 1), 1);
+        assert_eq!(table.kth(5, 3), 3);
+        assert_eq!(table.kth(0, 1_000_000_000_000_000_000), 4);
+
+        assert_eq!(table.first_meeting(3, 3, 0), Some(0));
+        assert_eq!(table.first_meeting(0, 3, 5), Some(2));
+        assert_eq!(table.first_meeting(0, 3, 1), None);
+        assert_eq!(table.first_meeting(5, 0, 100), Some(1));
+        assert_eq!(table.first_meeting(0, 2, 100), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_too_many_steps() {
+        let table = SuccessorTable::new(&[0], 3);
+        table.kth(0, 8);
+    }
+}
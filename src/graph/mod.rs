@@ -3,14 +3,26 @@
 //! # Panics
 //!
 //! All methods will panic if given an out-of-bounds element index.
+mod coloring;
 pub mod connectivity;
-mod dfs;
+mod dag;
+pub mod dfs;
+pub mod exponential;
 pub mod flow;
+pub mod functional;
+pub mod shortest_paths;
+pub mod spanning_tree;
+
+use std::collections::HashMap;
 
 /// Represents a union of disjoint sets. Each set's elements are arranged in a
 /// tree, whose root is the set's representative.
 pub struct DisjointSets {
     parent: Vec<usize>,
+    /// Number of elements in each root's set.
+    size: Vec<usize>,
+    /// Number of disjoint sets.
+    num_sets: usize,
 }
 
 impl DisjointSets {
@@ -18,25 +30,185 @@ impl DisjointSets {
     pub fn new(size: usize) -> Self {
         Self {
             parent: (0..size).collect(),
+            size: vec![1; size],
+            num_sets: size,
         }
     }
 
     /// Finds the set's representative. Do path compression along the way to make
     /// future queries faster.
+    /// Iterative, so that long chains can't overflow the stack.
     pub fn find(&mut self, u: usize) -> usize {
-        let pu = self.parent[u];
-        if pu != u {
-            self.parent[u] = self.find(pu);
+        let mut root = u;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        let mut v = u;
+        while self.parent[v] != root {
+            let pv = self.parent[v];
+            self.parent[v] = root;
+            v = pv;
         }
-        self.parent[u]
+        root
     }
 
     /// Merges the sets containing u and v into a single set containing their
     /// union. Returns true if u and v were previously in different sets.
+    /// The smaller set's root goes under the other, so trees stay logarithmic.
     pub fn merge(&mut self, u: usize, v: usize) -> bool {
-        let (pu, pv) = (self.find(u), self.find(v));
+        let (mut pu, mut pv) = (self.find(u), self.find(v));
+        if pu == pv {
+            return false;
+        }
+        if self.size[pu] > self.size[pv] {
+            ::std::mem::swap(&mut pu, &mut pv);
+        }
+        self.size[pv] += self.size[pu];
         self.parent[pu] = pv;
-        pu != pv
+        self.num_sets -= 1;
+        true
+    }
+
+    /// Like merge(), but returns the representative of the merged set.
+    pub fn merge_root(&mut self, u: usize, v: usize) -> usize {
+        self.merge(u, v);
+        self.find(u)
+    }
+
+    /// Returns the number of elements in the set containing u.
+    pub fn size(&mut self, u: usize) -> usize {
+        let pu = self.find(u);
+        self.size[pu]
+    }
+
+    /// Returns the number of disjoint sets.
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// Imports the sets of other, with each element u renamed to u + offset.
+    /// Elements are appended as singletons if needed to make room. Renamed
+    /// elements already present keep their sets, which are merged with the
+    /// imported ones. O(n α(n)) for n elements in other.
+    pub fn extend_from(&mut self, other: &mut DisjointSets, offset: usize) {
+        let len = self.parent.len().max(offset + other.parent.len());
+        for u in self.parent.len()..len {
+            self.parent.push(u);
+            self.size.push(1);
+            self.num_sets += 1;
+        }
+        for u in 0..other.parent.len() {
+            let root = other.find(u);
+            self.merge(u + offset, root + offset);
+        }
+    }
+}
+
+/// Disjoint sets whose merges can be undone in LIFO order, as needed for
+/// offline dynamic connectivity. Path compression would make undoing merges
+/// expensive, so only union by size is used: each find() takes O(log n).
+pub struct RollbackDisjointSets {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    /// Stack of roots that were placed under another root, one per merge.
+    history: Vec<usize>,
+}
+
+impl RollbackDisjointSets {
+    /// Initializes disjoint sets containing one element each.
+    pub fn new(size: usize) -> Self {
+        Self {
+            parent: (0..size).collect(),
+            size: vec![1; size],
+            history: Vec::new(),
+        }
+    }
+
+    /// Finds the set's representative, without modifying the trees.
+    pub fn find(&self, mut u: usize) -> usize {
+        while self.parent[u] != u {
+            u = self.parent[u];
+        }
+        u
+    }
+
+    /// Merges the sets containing u and v into a single set containing their
+    /// union. Returns true if u and v were previously in different sets, in
+    /// which case the merge is recorded so that it can be rolled back.
+    pub fn merge(&mut self, u: usize, v: usize) -> bool {
+        let (mut pu, mut pv) = (self.find(u), self.find(v));
+        if pu == pv {
+            return false;
+        }
+        if self.size[pu] > self.size[pv] {
+            ::std::mem::swap(&mut pu, &mut pv);
+        }
+        self.size[pv] += self.size[pu];
+        self.parent[pu] = pv;
+        self.history.push(pu);
+        true
+    }
+
+    /// Returns the number of recorded merges, for use with rollback_to().
+    pub fn snapshot(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Undoes the most recent recorded merge.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no recorded merges.
+    pub fn rollback(&mut self) {
+        let pu = self.history.pop().expect("no merge to roll back");
+        let pv = self.parent[pu];
+        self.size[pv] -= self.size[pu];
+        self.parent[pu] = pu;
+    }
+
+    /// Undoes merges until only the first snapshot recorded merges remain.
+    pub fn rollback_to(&mut self, snapshot: usize) {
+        while self.history.len() > snapshot {
+            self.rollback();
+        }
+    }
+}
+
+/// An undirected graph that grows one edge at a time, answering connectivity
+/// queries between insertions in near-constant amortized time.
+pub struct IncrementalConnectivity {
+    graph: Graph,
+    components: DisjointSets,
+}
+
+impl IncrementalConnectivity {
+    /// Initializes a graph with vmax vertices and no edges, as in Graph::new().
+    pub fn new(vmax: usize, emax_hint: usize) -> Self {
+        Self {
+            graph: Graph::new_undirected(vmax, 2 * emax_hint),
+            components: DisjointSets::new(vmax),
+        }
+    }
+
+    /// Adds an undirected edge between u and v.
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        self.graph.add_undirected_edge(u, v);
+        self.components.merge(u, v);
+    }
+
+    /// Determines whether u and v are connected by a path.
+    pub fn connected(&mut self, u: usize, v: usize) -> bool {
+        self.components.find(u) == self.components.find(v)
+    }
+
+    /// Returns the number of connected components.
+    pub fn num_components(&self) -> usize {
+        self.components.num_sets()
+    }
+
+    /// Gets the graph of all edges added so far.
+    pub fn graph(&self) -> &Graph {
+        &self.graph
     }
 }
 
@@ -49,6 +221,11 @@ pub struct Graph {
     next: Vec<Option<usize>>,
     /// Maps an edge id to the vertex that it points to.
     endp: Vec<usize>,
+    /// Maps an edge id to its reverse, if they were added together as an
+    /// undirected edge.
+    reverse: Vec<Option<usize>>,
+    /// Whether add_edge() is forbidden, so that edges come in reverse pairs.
+    undirected_only: bool,
 }
 
 impl Graph {
@@ -60,6 +237,18 @@ impl Graph {
             first: vec![None; vmax],
             next: Vec::with_capacity(emax_hint),
             endp: Vec::with_capacity(emax_hint),
+            reverse: Vec::with_capacity(emax_hint),
+            undirected_only: false,
+        }
+    }
+
+    /// Like new(), but for a graph whose edges may only be added through
+    /// add_undirected_edge(), so that the reverse of every edge e is at e^1.
+    /// Calling add_edge() on it panics.
+    pub fn new_undirected(vmax: usize, emax_hint: usize) -> Self {
+        Self {
+            undirected_only: true,
+            ..Self::new(vmax, emax_hint)
         }
     }
 
@@ -75,16 +264,66 @@ impl Graph {
 
     /// Adds a directed edge from u to v.
     pub fn add_edge(&mut self, u: usize, v: usize) {
+        assert!(
+            !self.undirected_only,
+            "directed edge added to a graph from new_undirected()"
+        );
+        self.push_edge(u, v, None);
+    }
+
+    // Helper function used by add_edge, add_undirected_edge and add_residual_pair.
+    fn push_edge(&mut self, u: usize, v: usize, reverse: Option<usize>) {
         self.next.push(self.first[u]);
         self.first[u] = Some(self.num_e());
         self.endp.push(v);
+        self.reverse.push(reverse);
     }
 
     /// An undirected edge is two directed edges. If edges are added only via
     /// this funcion, the reverse of any edge e can be found at e^1.
     pub fn add_undirected_edge(&mut self, u: usize, v: usize) {
-        self.add_edge(u, v);
-        self.add_edge(v, u);
+        let e = self.num_e();
+        self.push_edge(u, v, Some(e + 1));
+        self.push_edge(v, u, Some(e));
+    }
+
+    /// Adds a directed edge from u to v along with its reverse from v to u, so
+    /// that the reverse of e is at e^1, but without marking them bidirectional.
+    /// Used for the residual edges of flow networks.
+    fn add_residual_pair(&mut self, u: usize, v: usize) {
+        self.push_edge(u, v, None);
+        self.push_edge(v, u, None);
+    }
+
+    /// For graphs mixing directed and undirected edges: adds an undirected
+    /// edge between u and v if bidirectional is true, or else a directed edge
+    /// from u to v.
+    pub fn add_mixed_edge(&mut self, u: usize, v: usize, bidirectional: bool) {
+        if bidirectional {
+            self.add_undirected_edge(u, v);
+        } else {
+            self.add_edge(u, v);
+        }
+    }
+
+    /// Determines whether edge e was added as half of an undirected edge.
+    pub fn is_bidirectional(&self, e: usize) -> bool {
+        self.reverse[e].is_some()
+    }
+
+    /// Finds the other half of the undirected edge containing e, or None if e
+    /// is directed. This is e^1 unless directed edges were added before e.
+    pub fn reverse_edge(&self, e: usize) -> Option<usize> {
+        self.reverse[e]
+    }
+
+    /// Panics unless every edge was added along with its reverse at e^1, as
+    /// required by the methods on undirected graphs that rely on it.
+    fn assert_undirected(&self) {
+        assert!(
+            self.undirected_only || (0..self.num_e()).all(|e| self.reverse[e] == Some(e ^ 1)),
+            "graph has directed edges, so reverses aren't at e^1"
+        );
     }
 
     /// If we think of each even-numbered vertex as a variable, and its
@@ -96,14 +335,106 @@ impl Graph {
         self.add_edge(v ^ 1, u);
     }
 
+    /// Maps each edge id to the vertex that it points from. The compact
+    /// representation doesn't store this, so it takes O(V + E) to recover.
+    fn tails(&self) -> Vec<usize> {
+        let mut tail = vec![0; self.num_e()];
+        for u in 0..self.num_v() {
+            for (e, _) in self.adj_list(u) {
+                tail[e] = u;
+            }
+        }
+        tail
+    }
+
+    /// Lists the ids of all edges from a vertex to itself, in increasing order.
+    /// An undirected self-loop contributes both of its directions.
+    pub fn self_loops(&self) -> Vec<usize> {
+        let tail = self.tails();
+        (0..self.num_e())
+            .filter(|&e| tail[e] == self.endp[e])
+            .collect()
+    }
+
+    /// Counts the directed edges from u to v for every pair (u, v) joined by at
+    /// least one edge. Undirected edges count once in each direction.
+    pub fn edge_multiplicities(&self) -> HashMap<(usize, usize), usize> {
+        let mut count = HashMap::new();
+        for (u, v) in self.tails().into_iter().zip(self.endp.iter().copied()) {
+            *count.entry((u, v)).or_insert(0) += 1;
+        }
+        count
+    }
+
+    /// Builds the k-th power of the graph, which has an edge from u to v iff
+    /// some walk from u to v has exactly k edges. Edges are added in order of
+    /// (u, v). The empty walk makes power(0) a self-loop at every vertex.
+    /// O(V^2 + Vk(V + E)).
+    pub fn power(&self, k: usize) -> Graph {
+        self.power_helper(k, false)
+    }
+
+    /// Like power(), but with an edge from u to v iff some walk from u to v
+    /// has between 1 and k edges, so power_within(0) has no edges.
+    pub fn power_within(&self, k: usize) -> Graph {
+        self.power_helper(k, true)
+    }
+
+    // Helper function used by power and power_within: steps along all walks
+    // from each source, keeping the set of their current endpoints.
+    fn power_helper(&self, k: usize, within: bool) -> Graph {
+        let n = self.num_v();
+        let mut reach = vec![vec![false; n]; n];
+        for (u, reach_u) in reach.iter_mut().enumerate() {
+            let mut cur = vec![false; n];
+            cur[u] = true;
+            for _ in 0..k {
+                let mut next = vec![false; n];
+                for w in (0..n).filter(|&w| cur[w]) {
+                    for (_, v) in self.adj_list(w) {
+                        next[v] = true;
+                    }
+                }
+                cur = next;
+                if within {
+                    for (r, &c) in reach_u.iter_mut().zip(&cur) {
+                        *r |= c;
+                    }
+                }
+            }
+            if !within {
+                *reach_u = cur;
+            }
+        }
+        let num_e = reach.iter().flatten().filter(|&&r| r).count();
+        let mut power = Graph::new(n, num_e);
+        for (u, reach_u) in reach.iter().enumerate() {
+            for v in (0..n).filter(|&v| reach_u[v]) {
+                power.add_edge(u, v);
+            }
+        }
+        power
+    }
+
     /// Gets vertex u's adjacency list.
-    pub fn adj_list(&self, u: usize) -> AdjListIterator {
+    pub fn adj_list(&self, u: usize) -> AdjListIterator<'_> {
         AdjListIterator {
             graph: self,
             next_e: self.first[u],
         }
     }
 
+    /// Gets vertex u's adjacency list, with each edge's weight alongside its id
+    /// and endpoint.
+    pub fn adj_list_weighted<'a>(
+        &'a self,
+        weights: &'a [i64],
+        u: usize,
+    ) -> impl Iterator<Item = (usize, usize, i64)> + 'a {
+        assert_eq!(self.num_e(), weights.len());
+        self.adj_list(u).map(move |(e, v)| (e, v, weights[e]))
+    }
+
     /// Finds the sequence of edges in an Euler path starting from u, assuming
     /// it exists and that the graph is directed. Undefined behavior if this
     /// precondition is violated. To extend this to undirected graphs, maintain
@@ -127,17 +458,109 @@ impl Graph {
         }
     }
 
+    /// Counts the Euler circuits of a directed graph modulo a prime, treating
+    /// rotations of the same circuit as equal. By the BEST theorem, this is the
+    /// number of spanning arborescences toward any fixed vertex, computed as a
+    /// Laplacian minor, times the product of (deg(v) - 1)! over all vertices
+    /// with edges. Returns 0 if some vertex has unequal in- and out-degree, or
+    /// if the edges don't all lie in one connected component. A graph without
+    /// edges has exactly one, empty, circuit. O(V^3 + E).
+    ///
+    /// # Panics
+    ///
+    /// Panics if modulus is 0. May panic on overflow if
+    /// modulus * modulus > 2^63.
+    pub fn count_eulerian_circuits_mod(&self, modulus: u64) -> u64 {
+        assert!(modulus > 0, "modulus must be positive");
+        let out_degree = (0..self.num_v())
+            .map(|u| self.adj_list(u).count() as u64)
+            .collect::<Vec<_>>();
+        let mut in_degree = vec![0; self.num_v()];
+        let mut components = DisjointSets::new(self.num_v());
+        for (&u, &v) in self.tails().iter().zip(&self.endp) {
+            in_degree[v] += 1;
+            components.merge(u, v);
+        }
+        if in_degree != out_degree {
+            return 0;
+        }
+        let active = (0..self.num_v())
+            .filter(|&u| out_degree[u] > 0)
+            .collect::<Vec<_>>();
+        let root = match active.first() {
+            Some(&u) => components.find(u),
+            None => return 1 % modulus,
+        };
+        if active.iter().any(|&u| components.find(u) != root) {
+            return 0;
+        }
+
+        // The Laplacian D - A, restricted to active vertices other than the
+        // first. Self-loops cancel out, as they should.
+        let mut index = vec![None; self.num_v()];
+        for (i, &u) in active.iter().enumerate().skip(1) {
+            index[u] = Some(i - 1);
+        }
+        let mut laplacian = vec![vec![0; active.len() - 1]; active.len() - 1];
+        for &u in &active[1..] {
+            let i = index[u].unwrap();
+            laplacian[i][i] = (laplacian[i][i] + out_degree[u]) % modulus;
+            for (_, v) in self.adj_list(u) {
+                if let Some(j) = index[v] {
+                    laplacian[i][j] = (laplacian[i][j] + modulus - 1) % modulus;
+                }
+            }
+        }
+
+        let mut count = crate::math::det_mod(laplacian, modulus);
+        for &u in &active {
+            for k in 1..out_degree[u] {
+                count = count * (k % modulus) % modulus;
+            }
+        }
+        count
+    }
+
     /// Kruskal's minimum spanning tree algorithm on an undirected graph.
     pub fn min_spanning_tree(&self, weights: &[i64]) -> Vec<usize> {
+        self.min_spanning_tree_with_callback(weights, |_, _, _| {})
+    }
+
+    /// Like min_spanning_tree(), but also returns the ids of the remaining
+    /// undirected edges in increasing order. Each of them closes a fundamental
+    /// cycle with the tree.
+    pub fn mst_with_nontree_edges(&self, weights: &[i64]) -> (Vec<usize>, Vec<usize>) {
+        let tree = self.min_spanning_tree(weights);
+        let mut in_tree = vec![false; weights.len()];
+        for &e in &tree {
+            in_tree[e] = true;
+        }
+        let nontree = (0..weights.len()).filter(|&e| !in_tree[e]).collect();
+        (tree, nontree)
+    }
+
+    /// Like min_spanning_tree(), but calls on_merge(e, num_components, step)
+    /// whenever edge e joins two components, where num_components is the
+    /// number remaining afterward and step is e's index in the result.
+    pub fn min_spanning_tree_with_callback<F: FnMut(usize, usize, usize)>(
+        &self,
+        weights: &[i64],
+        mut on_merge: F,
+    ) -> Vec<usize> {
         assert_eq!(self.num_e(), 2 * weights.len());
+        self.assert_undirected();
         let mut edges = (0..weights.len()).collect::<Vec<_>>();
         edges.sort_unstable_by_key(|&e| weights[e]);
 
         let mut components = DisjointSets::new(self.num_v());
-        edges
-            .into_iter()
-            .filter(|&e| components.merge(self.endp[2 * e], self.endp[2 * e + 1]))
-            .collect()
+        let mut tree = vec![];
+        for e in edges {
+            if components.merge(self.endp[2 * e], self.endp[2 * e + 1]) {
+                on_merge(e, components.num_sets(), tree.len());
+                tree.push(e);
+            }
+        }
+        tree
     }
 }
 
@@ -160,10 +583,274 @@ impl<'a> Iterator for AdjListIterator<'a> {
     }
 }
 
+/// A xorshift pseudorandom number generator, for reproducible randomized tests.
+#[cfg(test)]
+fn xorshift(mut seed: u64) -> impl FnMut() -> u64 {
+    move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_disjoint_sets_size() {
+        let mut sets = DisjointSets::new(4);
+        assert_eq!(sets.size(0), 1);
+        sets.merge(0, 1);
+        sets.merge(2, 1);
+
+        assert_eq!(sets.size(0), 3);
+        assert_eq!(sets.size(2), 3);
+        assert_eq!(sets.size(3), 1);
+    }
+
+    #[test]
+    fn test_disjoint_sets_merge_root() {
+        let mut sets = DisjointSets::new(3);
+        let root = sets.merge_root(0, 1);
+        assert_eq!(sets.find(0), root);
+        assert_eq!(sets.find(1), root);
+        assert_eq!(sets.merge_root(1, 0), root);
+        assert_eq!(sets.merge_root(2, 2), 2);
+    }
+
+    #[test]
+    fn test_disjoint_sets_count() {
+        let mut sets = DisjointSets::new(5);
+        assert_eq!(sets.num_sets(), 5);
+        assert!(sets.merge(0, 1));
+        assert!(sets.merge(3, 4));
+        assert!(!sets.merge(1, 0));
+
+        assert_eq!(sets.num_sets(), 3);
+    }
+
+    #[test]
+    fn test_disjoint_sets_extend_from() {
+        let mut sets = DisjointSets::new(3);
+        sets.merge(0, 1);
+        let mut other = DisjointSets::new(3);
+        other.merge(1, 2);
+        sets.extend_from(&mut other, 3);
+        assert_eq!(sets.num_sets(), 4);
+        assert_eq!(sets.size(5), 2);
+        assert_eq!(sets.find(4), sets.find(5));
+        assert_ne!(sets.find(3), sets.find(4));
+
+        // Merges across the two ranges behave as if built in one structure.
+        assert!(sets.merge(2, 3));
+        assert!(sets.merge(1, 4));
+        assert!(!sets.merge(0, 5));
+        assert_eq!(sets.num_sets(), 2);
+        assert_eq!(sets.size(0), 4);
+        assert_eq!(sets.size(3), 2);
+
+        // Overlapping imports merge into the existing sets.
+        let mut overlap = DisjointSets::new(2);
+        overlap.merge(0, 1);
+        sets.extend_from(&mut overlap, 5);
+        assert_eq!(sets.num_sets(), 2);
+        assert_eq!(sets.size(6), 5);
+    }
+
+    #[test]
+    fn test_disjoint_sets_long_chain() {
+        let n = 1_000_000;
+        let mut sets = DisjointSets::new(n);
+        // Union by size would keep merged trees shallow, so link the chain
+        // 0 -> 1 -> ... -> n - 1 by hand.
+        for i in 0..n - 1 {
+            sets.parent[i] = i + 1;
+        }
+        sets.size[n - 1] = n;
+        sets.num_sets = 1;
+
+        assert_eq!(sets.find(0), n - 1);
+        assert!(sets.parent.iter().all(|&p| p == n - 1));
+        assert_eq!(sets.size(0), n);
+    }
+
+    #[test]
+    fn test_disjoint_sets_balance() {
+        let n = 1 << 10;
+        let mut sets = DisjointSets::new(n);
+        // Without union by size, this order would build a chain of length n.
+        for i in 1..n {
+            assert!(sets.merge(i - 1, i));
+        }
+
+        // Measure tree heights by walking parents, without path compression.
+        let height = |mut u: usize| {
+            let mut h = 0;
+            while sets.parent[u] != u {
+                u = sets.parent[u];
+                h += 1;
+            }
+            h
+        };
+        assert!((0..n).all(|u| height(u) <= 10));
+    }
+
+    #[test]
+    fn test_rollback_disjoint_sets() {
+        let mut sets = RollbackDisjointSets::new(5);
+        assert!(sets.merge(0, 1));
+        assert!(sets.merge(2, 3));
+        let snapshot = sets.snapshot();
+
+        assert!(sets.merge(1, 2));
+        assert!(sets.merge(3, 4));
+        assert!(!sets.merge(0, 4));
+        assert_eq!(sets.find(0), sets.find(4));
+
+        sets.rollback_to(snapshot);
+        assert_eq!(sets.snapshot(), snapshot);
+        assert_eq!(sets.find(0), sets.find(1));
+        assert_eq!(sets.find(2), sets.find(3));
+        assert_ne!(sets.find(1), sets.find(2));
+        assert_ne!(sets.find(3), sets.find(4));
+
+        sets.rollback();
+        assert_ne!(sets.find(2), sets.find(3));
+        assert_eq!(sets.find(0), sets.find(1));
+    }
+
+    #[test]
+    fn test_self_loops() {
+        let mut graph = Graph::new(3, 6);
+        graph.add_edge(0, 1);
+        graph.add_edge(2, 2);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(0, 0);
+        assert_eq!(graph.self_loops(), vec![1, 4, 5]);
+        assert!(Graph::new(2, 0).self_loops().is_empty());
+    }
+
+    #[test]
+    fn test_edge_multiplicities() {
+        let mut graph = Graph::new(3, 6);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(0, 1);
+        graph.add_undirected_edge(2, 2);
+
+        let count = graph.edge_multiplicities();
+        assert_eq!(count.len(), 3);
+        assert_eq!(count[&(0, 1)], 3);
+        assert_eq!(count[&(1, 2)], 1);
+        assert_eq!(count[&(2, 2)], 2);
+        assert_eq!(count.get(&(1, 0)), None);
+    }
+
+    #[test]
+    fn test_new_undirected() {
+        let mut graph = Graph::new_undirected(4, 6);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(2, 1);
+        graph.add_undirected_edge(3, 3);
+        for (e, u) in graph.tails().into_iter().enumerate() {
+            assert_eq!(u, graph.endp[e ^ 1]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_undirected_rejects_directed_edge() {
+        let mut graph = Graph::new_undirected(2, 1);
+        graph.add_edge(0, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "reverses aren't at e^1")]
+    fn test_mst_rejects_directed_edges() {
+        let mut graph = Graph::new(3, 4);
+        graph.add_undirected_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        graph.min_spanning_tree(&[1, 1]);
+    }
+
+    #[test]
+    fn test_add_mixed_edge() {
+        let mut graph = Graph::new(3, 3);
+        graph.add_mixed_edge(0, 1, false);
+        graph.add_mixed_edge(1, 2, true);
+        assert_eq!(graph.num_e(), 3);
+        assert!(!graph.is_bidirectional(0));
+        assert!(graph.is_bidirectional(1));
+        assert!(graph.is_bidirectional(2));
+        assert_eq!((graph.endp[1], graph.endp[2]), (2, 1));
+        // The directed edge shifts the undirected pair off the e^1 pattern.
+        assert_eq!(graph.reverse_edge(0), None);
+        assert_eq!(graph.reverse_edge(1), Some(2));
+        assert_eq!(graph.reverse_edge(2), Some(1));
+        assert_eq!(graph.adj_list(1).collect::<Vec<_>>(), vec![(1, 2)]);
+    }
+
+    #[test]
+    fn test_adj_list_weighted() {
+        let mut graph = Graph::new(3, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(0, 2);
+        graph.add_edge(0, 0);
+        let weights = [5, -1, 7, 2];
+
+        let adj = graph.adj_list_weighted(&weights, 0).collect::<Vec<_>>();
+        assert_eq!(adj, vec![(3, 0, 2), (2, 2, 7), (0, 1, 5)]);
+        assert!(adj.iter().all(|&(e, _, w)| weights[e] == w));
+        assert_eq!(graph.adj_list_weighted(&weights, 2).count(), 0);
+    }
+
+    #[test]
+    fn test_power() {
+        let mut chain = Graph::new(4, 3);
+        chain.add_edge(0, 1);
+        chain.add_edge(1, 2);
+        chain.add_edge(2, 3);
+        let edges = |graph: &Graph| {
+            (0..graph.num_v())
+                .flat_map(|u| graph.adj_list(u).map(move |(_, v)| (u, v)))
+                .collect::<Vec<_>>()
+        };
+
+        let mut squared = edges(&chain.power(2));
+        squared.sort_unstable();
+        assert_eq!(squared, vec![(0, 2), (1, 3)]);
+
+        let mut within = edges(&chain.power_within(2));
+        within.sort_unstable();
+        assert_eq!(within, vec![(0, 1), (0, 2), (1, 2), (1, 3), (2, 3)]);
+
+        assert_eq!(edges(&chain.power(0)), vec![(0, 0), (1, 1), (2, 2), (3, 3)]);
+        assert_eq!(chain.power_within(0).num_e(), 0);
+        assert_eq!(chain.power(4).num_e(), 0);
+    }
+
+    #[test]
+    fn test_incremental_connectivity() {
+        let mut conn = IncrementalConnectivity::new(5, 4);
+        assert_eq!(conn.num_components(), 5);
+        assert!(!conn.connected(0, 2));
+
+        let steps = [((0, 1), 4), ((2, 3), 3), ((1, 0), 3), ((1, 2), 2)];
+        for &((u, v), num_components) in &steps {
+            conn.add_edge(u, v);
+            assert!(conn.connected(u, v));
+            assert_eq!(conn.num_components(), num_components);
+        }
+        assert!(conn.connected(0, 3));
+        assert!(!conn.connected(3, 4));
+        assert_eq!(conn.graph().num_e(), 8);
+    }
+
     #[test]
     fn test_euler() {
         let mut graph = Graph::new(3, 4);
@@ -175,6 +862,45 @@ mod test {
         assert_eq!(graph.euler_path(0), vec![0, 2, 3, 1]);
     }
 
+    #[test]
+    fn test_count_eulerian_circuits() {
+        let p = 1_000_000_007;
+        let mut cycle = Graph::new(3, 3);
+        cycle.add_edge(0, 1);
+        cycle.add_edge(1, 2);
+        cycle.add_edge(2, 0);
+        assert_eq!(cycle.count_eulerian_circuits_mod(p), 1);
+
+        // After fixing the first edge, each return to 0 has 2 choices, then 1.
+        let mut multi = Graph::new(2, 4);
+        multi.add_edge(0, 1);
+        multi.add_edge(0, 1);
+        multi.add_edge(1, 0);
+        multi.add_edge(1, 0);
+        assert_eq!(multi.count_eulerian_circuits_mod(p), 2);
+
+        let mut complete = Graph::new(3, 6);
+        complete.add_undirected_edge(0, 1);
+        complete.add_undirected_edge(1, 2);
+        complete.add_undirected_edge(2, 0);
+        assert_eq!(complete.count_eulerian_circuits_mod(p), 3);
+
+        let mut loops = Graph::new(2, 2);
+        loops.add_edge(1, 1);
+        loops.add_edge(1, 1);
+        assert_eq!(loops.count_eulerian_circuits_mod(p), 1);
+
+        let mut disconnected = Graph::new(4, 4);
+        disconnected.add_edge(0, 1);
+        disconnected.add_edge(1, 0);
+        disconnected.add_edge(2, 3);
+        disconnected.add_edge(3, 2);
+        assert_eq!(disconnected.count_eulerian_circuits_mod(p), 0);
+
+        cycle.add_edge(0, 2);
+        assert_eq!(cycle.count_eulerian_circuits_mod(p), 0);
+    }
+
     #[test]
     fn test_min_spanning_tree() {
         let mut graph = Graph::new(3, 3);
@@ -188,4 +914,35 @@ mod test {
         assert_eq!(mst, vec![1, 2]);
         assert_eq!(mst_cost, 8);
     }
+
+    #[test]
+    fn test_min_spanning_tree_with_callback() {
+        let mut graph = Graph::new(5, 10);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(1, 3);
+        let weights = [4, 1, 2, 3, 5];
+
+        let mut calls = vec![];
+        let mst = graph.min_spanning_tree_with_callback(&weights, |e, num_components, step| {
+            calls.push((e, num_components, step))
+        });
+        assert_eq!(mst, vec![1, 2, 3, 4]);
+        assert_eq!(calls, vec![(1, 4, 0), (2, 3, 1), (3, 2, 2), (4, 1, 3)]);
+    }
+
+    #[test]
+    fn test_mst_with_nontree_edges() {
+        let mut graph = Graph::new(3, 6);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+
+        let (tree, nontree) = graph.mst_with_nontree_edges(&[2, 3, 1]);
+        assert_eq!(tree, vec![2, 0]);
+        assert_eq!(nontree, vec![1]);
+        assert_eq!(graph.fundamental_cycles(&tree).len(), nontree.len());
+    }
 }
@@ -0,0 +1,1422 @@
+//! Single-source and all-pairs shortest path algorithms.
+use super::Graph;
+use crate::math::XorBasis;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, BinaryHeap, VecDeque};
+use std::ops::Add;
+
+/// Error returned when a negative-weight cycle makes shortest paths undefined.
+/// Contains the sequence of vertices along one such cycle.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NegativeCycle(pub Vec<usize>);
+
+impl Graph {
+    /// Breadth-first search: finds the number of edges on a shortest path from
+    /// source to every vertex, along with the last edge on such a path, so that
+    /// the BFS tree can be reconstructed with path_from_pred(). Unreachable
+    /// vertices have distance None. O(V + E).
+    pub fn bfs(&self, source: usize) -> (Vec<Option<u32>>, Vec<Option<usize>>) {
+        let mut dist = vec![None; self.num_v()];
+        let mut pred = vec![None; self.num_v()];
+        let mut q = VecDeque::new();
+
+        dist[source] = Some(0);
+        q.push_back(source);
+        while let Some(u) = q.pop_front() {
+            for (e, v) in self.adj_list(u) {
+                if dist[v].is_none() {
+                    dist[v] = dist[u].map(|d| d + 1);
+                    pred[v] = Some(e);
+                    q.push_back(v);
+                }
+            }
+        }
+        (dist, pred)
+    }
+
+    /// Finds a vertex reachable from src at the greatest BFS distance, along
+    /// with that distance, breaking ties by smallest id. In a tree, the
+    /// farthest vertex from any source is an endpoint of a diameter, so two
+    /// calls find the diameter. O(V + E).
+    pub fn farthest_vertex(&self, src: usize) -> (usize, u32) {
+        let dist = self.bfs(src).0;
+        let (d, Reverse(v)) = (0..self.num_v())
+            .filter_map(|v| dist[v].map(|d| (d, Reverse(v))))
+            .max()
+            .unwrap();
+        (v, d)
+    }
+
+    /// Double-sweep heuristic for the diameter of an undirected graph: runs
+    /// farthest_vertex() from vertex 0, then again from the vertex it finds.
+    /// The result is exact for trees; otherwise, it's a lower bound on the
+    /// diameter of vertex 0's component, and at least half of it. O(V + E).
+    pub fn approx_diameter(&self) -> u32 {
+        if self.num_v() == 0 {
+            return 0;
+        }
+        let (end, _) = self.farthest_vertex(0);
+        self.farthest_vertex(end).1
+    }
+
+    /// Runs bfs() from every vertex, so that entry [u][v] of the result is the
+    /// number of edges on a shortest path from u to v. O(V(V + E)).
+    pub fn all_pairs_bfs(&self) -> Vec<Vec<Option<u32>>> {
+        (0..self.num_v()).map(|u| self.bfs(u).0).collect()
+    }
+
+    /// Like all_pairs_bfs(), but splits the sources into contiguous chunks, one
+    /// per available CPU, searched in parallel on scoped threads. The result
+    /// doesn't depend on the number of threads.
+    pub fn all_pairs_bfs_parallel(&self) -> Vec<Vec<Option<u32>>> {
+        let num_threads = ::std::thread::available_parallelism().map_or(1, |n| n.get());
+        self.all_pairs_bfs_threads(num_threads)
+    }
+
+    // Helper function used by all_pairs_bfs_parallel.
+    fn all_pairs_bfs_threads(&self, num_threads: usize) -> Vec<Vec<Option<u32>>> {
+        let chunk_size = self.num_v().div_ceil(num_threads).max(1);
+        let sources = (0..self.num_v()).collect::<Vec<_>>();
+        ::std::thread::scope(|scope| {
+            let handles = sources
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(move || chunk.iter().map(|&u| self.bfs(u).0).collect::<Vec<_>>())
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+    }
+
+    /// Like bfs(), but finds the number of edges on a shortest path to every
+    /// vertex from the nearest of several sources, by starting the queue with
+    /// all of them at distance 0. O(V + E).
+    pub fn multi_source_bfs(&self, sources: &[usize]) -> Vec<Option<u32>> {
+        let mut dist = vec![None; self.num_v()];
+        let mut q = VecDeque::new();
+        for &s in sources {
+            if dist[s].is_none() {
+                dist[s] = Some(0);
+                q.push_back(s);
+            }
+        }
+        while let Some(u) = q.pop_front() {
+            for (_, v) in self.adj_list(u) {
+                if dist[v].is_none() {
+                    dist[v] = dist[u].map(|d| d + 1);
+                    q.push_back(v);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Finds the minimum XOR of edge weights over all walks from s to t in an
+    /// undirected graph, where each edge's two directions have equal weights,
+    /// or None if t is unreachable. Walking around a cycle and back toggles
+    /// its XOR, so the answer is the XOR along any one path, minimized over the
+    /// cycle space: the span of every non-tree edge's fundamental cycle XOR.
+    /// O(V + 64E).
+    pub fn min_xor_path(&self, weights: &[u64], s: usize, t: usize) -> Option<u64> {
+        assert_eq!(self.num_e(), weights.len());
+        let mut xor = vec![None; self.num_v()];
+        let mut basis = XorBasis::new();
+        let mut q = VecDeque::from([s]);
+
+        xor[s] = Some(0);
+        while let Some(u) = q.pop_front() {
+            let xor_u = xor[u].unwrap();
+            for (e, v) in self.adj_list(u) {
+                match xor[v] {
+                    Some(xor_v) => {
+                        basis.insert(xor_u ^ weights[e] ^ xor_v);
+                    }
+                    None => {
+                        xor[v] = Some(xor_u ^ weights[e]);
+                        q.push_back(v);
+                    }
+                }
+            }
+        }
+        xor[t].map(|x| basis.min_with(x))
+    }
+
+    /// 0-1 BFS: like Dijkstra's algorithm, but specialized to edge weights of
+    /// 0 or 1 so that a deque can replace the heap. O(V + E).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any weight exceeds 1.
+    pub fn zero_one_bfs(&self, weights: &[u8], source: usize) -> Vec<Option<u64>> {
+        assert_eq!(self.num_e(), weights.len());
+        assert!(weights.iter().all(|&w| w <= 1), "weights must be 0 or 1");
+        let mut dist = vec![None; self.num_v()];
+        let mut q = VecDeque::new();
+
+        dist[source] = Some(0);
+        q.push_back(source);
+        while let Some(u) = q.pop_front() {
+            let dist_u = dist[u].unwrap();
+            for (e, v) in self.adj_list(u) {
+                let dist_v = dist_u + u64::from(weights[e]);
+                if dist[v].is_none_or(|d| d > dist_v) {
+                    dist[v] = Some(dist_v);
+                    if weights[e] == 0 {
+                        q.push_front(v);
+                    } else {
+                        q.push_back(v);
+                    }
+                }
+            }
+        }
+        dist
+    }
+
+    /// Dijkstra's algorithm with a binary heap: finds the distance from source
+    /// to every vertex, along with the last edge on a shortest path to it.
+    /// Unreachable vertices have distance None. O((V + E) log V).
+    ///
+    /// Weights may be of any integer type, with W::default() as zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any weight is negative, since a negative cycle would make the
+    /// search run forever or overflow.
+    pub fn dijkstra<W>(&self, weights: &[W], source: usize) -> (Vec<Option<W>>, Vec<Option<usize>>)
+    where
+        W: Copy + Ord + Default + Add<Output = W>,
+    {
+        self.dijkstra_filtered(weights, source, |_, _| true)
+    }
+
+    // Helper function used by dijkstra: only the edges (e, v) accepted by the
+    // filter are traversed.
+    fn dijkstra_filtered<W, F>(
+        &self,
+        weights: &[W],
+        source: usize,
+        filter: F,
+    ) -> (Vec<Option<W>>, Vec<Option<usize>>)
+    where
+        W: Copy + Ord + Default + Add<Output = W>,
+        F: Fn(usize, usize) -> bool,
+    {
+        assert_eq!(self.num_e(), weights.len());
+        assert!(
+            weights.iter().all(|&w| w >= W::default()),
+            "Dijkstra's algorithm requires nonnegative weights"
+        );
+        let mut dist = vec![None; self.num_v()];
+        let mut pred = vec![None; self.num_v()];
+        let mut heap = BinaryHeap::new();
+
+        dist[source] = Some(W::default());
+        heap.push(Reverse((W::default(), source)));
+        while let Some(Reverse((dist_u, u))) = heap.pop() {
+            // Skip stale heap entries, superseded by a later improvement.
+            if dist[u] < Some(dist_u) {
+                continue;
+            }
+            for (e, v) in self.adj_list(u).filter(|&(e, v)| filter(e, v)) {
+                let dist_v = dist_u + weights[e];
+                if dist[v].is_none_or(|d| d > dist_v) {
+                    dist[v] = Some(dist_v);
+                    pred[v] = Some(e);
+                    heap.push(Reverse((dist_v, v)));
+                }
+            }
+        }
+        (dist, pred)
+    }
+
+    /// Finds a shortest path from src to dst by dijkstra(), as its sequence of
+    /// edge ids, or None if dst is unreachable. Weights must be nonnegative.
+    pub fn dijkstra_path_edges(
+        &self,
+        weights: &[i64],
+        src: usize,
+        dst: usize,
+    ) -> Option<Vec<usize>> {
+        let (dist, pred) = self.dijkstra(weights, src);
+        dist[dst].map(|_| self.path_from_pred(&pred, dst))
+    }
+
+    /// Finds the length of a shortest walk from src to every vertex using an
+    /// even number of edges if even is true, or an odd number otherwise, by
+    /// running dijkstra() on the graph of (vertex, parity) pairs. Weights must
+    /// be nonnegative. O((V + E) log V).
+    pub fn shortest_path_parity(
+        &self,
+        weights: &[i64],
+        src: usize,
+        even: bool,
+    ) -> Vec<Option<i64>> {
+        assert_eq!(self.num_e(), weights.len());
+        let mut doubled = Graph::new(2 * self.num_v(), 2 * self.num_e());
+        for (u, v) in self.tails().into_iter().zip(&self.endp) {
+            doubled.add_edge(2 * u, 2 * v + 1);
+            doubled.add_edge(2 * u + 1, 2 * v);
+        }
+        let doubled_weights = weights.iter().flat_map(|&w| [w, w]).collect::<Vec<_>>();
+        let dist = doubled.dijkstra(&doubled_weights, 2 * src).0;
+        let parity = if even { 0 } else { 1 };
+        (0..self.num_v()).map(|v| dist[2 * v + parity]).collect()
+    }
+
+    /// Builds the graph of every edge (u, v) on some shortest path from src,
+    /// i.e., with dist[u] + w(u, v) == dist[v] as found by dijkstra(). Its
+    /// paths from src are exactly the shortest paths, and it's a DAG unless
+    /// some cycle has zero weight. Edges keep their relative order. Weights
+    /// must be nonnegative. O((V + E) log V).
+    pub fn shortest_path_dag(&self, weights: &[i64], src: usize) -> Graph {
+        let dist = self.dijkstra(weights, src).0;
+        let tight = self
+            .tails()
+            .into_iter()
+            .zip(&self.endp)
+            .zip(weights)
+            .filter(
+                |&((u, &v), &w)| matches!((dist[u], dist[v]), (Some(du), Some(dv)) if du + w == dv),
+            )
+            .map(|((u, &v), _)| (u, v))
+            .collect::<Vec<_>>();
+        let mut dag = Graph::new(self.num_v(), tight.len());
+        for (u, v) in tight {
+            dag.add_edge(u, v);
+        }
+        dag
+    }
+
+    /// Counts the shortest paths from source to every vertex, modulo the given
+    /// modulus, along with their distances as in dijkstra(). Every weight must
+    /// be positive: with zero-weight edges, the counts may be wrong.
+    ///
+    /// # Panics
+    ///
+    /// Panics if modulus is 0.
+    pub fn count_shortest_paths(
+        &self,
+        weights: &[u64],
+        source: usize,
+        modulus: u64,
+    ) -> (Vec<Option<u64>>, Vec<u64>) {
+        assert_eq!(self.num_e(), weights.len());
+        assert!(modulus > 0, "modulus must be positive");
+        let mut dist = vec![None; self.num_v()];
+        let mut count = vec![0; self.num_v()];
+        let mut heap = BinaryHeap::new();
+
+        dist[source] = Some(0);
+        count[source] = 1 % modulus;
+        heap.push(Reverse((0, source)));
+        while let Some(Reverse((dist_u, u))) = heap.pop() {
+            // Each vertex is pushed at most once per distinct distance, so when
+            // the stale entries are skipped, count[u] is added exactly once.
+            if dist[u] < Some(dist_u) {
+                continue;
+            }
+            for (e, v) in self.adj_list(u) {
+                let dist_v = dist_u + weights[e];
+                match dist[v] {
+                    Some(d) if d < dist_v => {}
+                    Some(d) if d == dist_v => {
+                        // Both counts are below modulus, but their sum may
+                        // overflow if modulus exceeds 2^63.
+                        let room = modulus - count[u];
+                        count[v] = if count[v] >= room {
+                            count[v] - room
+                        } else {
+                            count[v] + count[u]
+                        };
+                    }
+                    _ => {
+                        dist[v] = Some(dist_v);
+                        count[v] = count[u];
+                        heap.push(Reverse((dist_v, v)));
+                    }
+                }
+            }
+        }
+        (dist, count)
+    }
+
+    /// Yen's algorithm: finds the k shortest simple paths from source to
+    /// target, as pairs of total weight and edge sequence, sorted by weight.
+    /// If fewer than k simple paths exist, all of them are returned.
+    /// Runs O(kV) instances of Dijkstra's algorithm.
+    pub fn k_shortest_paths(
+        &self,
+        weights: &[u64],
+        source: usize,
+        target: usize,
+        k: usize,
+    ) -> Vec<(u64, Vec<usize>)> {
+        let tail = self.tails();
+        let mut paths = Vec::with_capacity(k);
+        if k == 0 {
+            return paths;
+        }
+        let mut candidates = BTreeSet::new();
+        let (dist, pred) = self.dijkstra(weights, source);
+        if let Some(d) = dist[target] {
+            candidates.insert((d, Self::walk_pred(&tail, &pred, target)));
+        }
+
+        while let Some(candidate) = candidates.pop_first() {
+            paths.push(candidate);
+            if paths.len() == k {
+                break;
+            }
+            // Deviate from the newest path at each of its vertices, or spurs.
+            let path = &paths[paths.len() - 1].1;
+            for i in 0..path.len() {
+                let (root, spur) = (&path[..i], tail[path[i]]);
+                let mut banned_e = vec![false; self.num_e()];
+                let mut banned_v = vec![false; self.num_v()];
+                for (_, other) in &paths {
+                    if other.len() > i && other[..i] == *root {
+                        banned_e[other[i]] = true;
+                    }
+                }
+                for &e in root {
+                    banned_v[tail[e]] = true;
+                }
+
+                let (dist, pred) =
+                    self.dijkstra_filtered(weights, spur, |e, v| !banned_e[e] && !banned_v[v]);
+                if let Some(d) = dist[target] {
+                    let root_cost = root.iter().map(|&e| weights[e]).sum::<u64>();
+                    let mut new_path = root.to_vec();
+                    new_path.extend(Self::walk_pred(&tail, &pred, target));
+                    candidates.insert((root_cost + d, new_path));
+                }
+            }
+        }
+        paths
+    }
+
+    /// A* search: finds the distance and a shortest sequence of edges from
+    /// source to target, or None if unreachable. The heuristic must never
+    /// overestimate the distance to target; if it's zero, this is Dijkstra's
+    /// algorithm with early termination. Also returns the number of vertex
+    /// expansions, which a better heuristic tends to reduce.
+    pub fn a_star<F: Fn(usize) -> u64>(
+        &self,
+        weights: &[u64],
+        source: usize,
+        target: usize,
+        heuristic: F,
+    ) -> (Option<(u64, Vec<usize>)>, usize) {
+        assert_eq!(self.num_e(), weights.len());
+        let mut dist = vec![None; self.num_v()];
+        let mut pred = vec![None; self.num_v()];
+        let mut heap = BinaryHeap::new();
+        let mut expanded = 0;
+
+        dist[source] = Some(0);
+        heap.push(Reverse((heuristic(source), 0, source)));
+        while let Some(Reverse((_, dist_u, u))) = heap.pop() {
+            // Vertices may be expanded again if the heuristic is inconsistent.
+            if dist[u] < Some(dist_u) {
+                continue;
+            }
+            if u == target {
+                let path = self.path_from_pred(&pred, target);
+                return (Some((dist_u, path)), expanded);
+            }
+            expanded += 1;
+            for (e, v) in self.adj_list(u) {
+                let dist_v = dist_u + weights[e];
+                if dist[v].is_none_or(|d| d > dist_v) {
+                    dist[v] = Some(dist_v);
+                    pred[v] = Some(e);
+                    heap.push(Reverse((dist_v + heuristic(v), dist_v, v)));
+                }
+            }
+        }
+        (None, expanded)
+    }
+
+    /// Bellman-Ford algorithm: finds the distance from source to every vertex,
+    /// allowing negative weights. If a negative cycle is reachable from source,
+    /// returns it as an error instead. O(VE).
+    pub fn bellman_ford(
+        &self,
+        weights: &[i64],
+        source: usize,
+    ) -> Result<Vec<Option<i64>>, NegativeCycle> {
+        assert_eq!(self.num_e(), weights.len());
+        let tail = self.tails();
+        let mut dist = vec![None; self.num_v()];
+        let mut pred = vec![None; self.num_v()];
+
+        dist[source] = Some(0);
+        let mut last_relaxed = None;
+        for _ in 0..self.num_v() {
+            last_relaxed = None;
+            for e in 0..self.num_e() {
+                let v = self.endp[e];
+                if let Some(dist_u) = dist[tail[e]] {
+                    let dist_v = dist_u + weights[e];
+                    if dist[v].is_none_or(|d| d > dist_v) {
+                        dist[v] = Some(dist_v);
+                        pred[v] = Some(e);
+                        last_relaxed = Some(v);
+                    }
+                }
+            }
+            if last_relaxed.is_none() {
+                return Ok(dist);
+            }
+        }
+
+        // A relaxation in round V means a negative cycle. Walking V steps back
+        // along predecessors is guaranteed to land on it.
+        let mut u = last_relaxed.unwrap();
+        for _ in 0..self.num_v() {
+            u = tail[pred[u].unwrap()];
+        }
+        let mut cycle = vec![u];
+        let mut v = tail[pred[u].unwrap()];
+        while v != u {
+            cycle.push(v);
+            v = tail[pred[v].unwrap()];
+        }
+        cycle.reverse();
+        Err(NegativeCycle(cycle))
+    }
+
+    /// Shortest Path Faster Algorithm: a queue-based Bellman-Ford that only
+    /// rescans the edges of vertices whose distance just improved, with the
+    /// small-label-first heuristic of putting a vertex at the front of the
+    /// queue if it's closer than the current front. Usually much faster than
+    /// bellman_ford(), but still O(VE) in the worst case.
+    ///
+    /// Aborts once some tentative shortest path has V edges, which implies a
+    /// negative cycle, returning a vertex on or reachable from that cycle.
+    pub fn spfa(&self, weights: &[i64], source: usize) -> Result<Vec<Option<i64>>, usize> {
+        assert_eq!(self.num_e(), weights.len());
+        let mut dist: Vec<Option<i64>> = vec![None; self.num_v()];
+        let mut num_edges = vec![0; self.num_v()];
+        let mut in_queue = vec![false; self.num_v()];
+        let mut q = VecDeque::from([source]);
+
+        dist[source] = Some(0);
+        in_queue[source] = true;
+        while let Some(u) = q.pop_front() {
+            // Clear the flag before scanning, so that u is queued again if one
+            // of its own out-edges (via a cycle) improves it.
+            in_queue[u] = false;
+            let dist_u = dist[u].unwrap();
+            for (e, v) in self.adj_list(u) {
+                let dist_v = dist_u + weights[e];
+                if dist[v].is_none_or(|d| d > dist_v) {
+                    dist[v] = Some(dist_v);
+                    num_edges[v] = num_edges[u] + 1;
+                    if num_edges[v] >= self.num_v() {
+                        return Err(v);
+                    }
+                    if !in_queue[v] {
+                        in_queue[v] = true;
+                        match q.front() {
+                            Some(&w) if dist[w].unwrap() > dist_v => q.push_front(v),
+                            _ => q.push_back(v),
+                        }
+                    }
+                }
+            }
+        }
+        Ok(dist)
+    }
+
+    /// Floyd-Warshall algorithm: finds the distance between every pair of
+    /// vertices, along with next[u][v], the vertex following u on a shortest
+    /// path to v. If there's a negative cycle, the results are meaningful only
+    /// for detecting it with has_negative_cycle(). O(V^3).
+    #[allow(clippy::type_complexity)]
+    pub fn floyd_warshall(
+        &self,
+        weights: &[i64],
+    ) -> (Vec<Vec<Option<i64>>>, Vec<Vec<Option<usize>>>) {
+        let n = self.num_v();
+        let mut dist = self.to_cost_matrix(weights);
+        let mut next = (0..n)
+            .map(|u| (0..n).map(|v| dist[u][v].map(|_| v)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        for k in 0..n {
+            for u in 0..n {
+                if let Some(dist_uk) = dist[u][k] {
+                    for v in 0..n {
+                        if let Some(dist_kv) = dist[k][v] {
+                            if dist[u][v].is_none_or(|d| d > dist_uk + dist_kv) {
+                                dist[u][v] = Some(dist_uk + dist_kv);
+                                next[u][v] = next[u][k];
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        (dist, next)
+    }
+
+    /// Finds the distance from source to target over paths of at most max_edges
+    /// edges, or None if there is no such path. Negative weights are allowed,
+    /// even in cycles. Each round of Bellman-Ford reads distances from the
+    /// previous round only, so that no path grows by more than one edge per
+    /// round. O(max_edges * (V + E)).
+    pub fn shortest_path_bounded(
+        &self,
+        weights: &[i64],
+        source: usize,
+        target: usize,
+        max_edges: usize,
+    ) -> Option<i64> {
+        assert_eq!(self.num_e(), weights.len());
+        let tail = self.tails();
+        let mut dist = vec![None; self.num_v()];
+        dist[source] = Some(0);
+        for _ in 0..max_edges {
+            let mut next_dist = dist.clone();
+            for e in 0..self.num_e() {
+                let v = self.endp[e];
+                if let Some(dist_u) = dist[tail[e]] {
+                    if next_dist[v].is_none_or(|d| d > dist_u + weights[e]) {
+                        next_dist[v] = Some(dist_u + weights[e]);
+                    }
+                }
+            }
+            dist = next_dist;
+        }
+        dist[target]
+    }
+
+    /// Johnson's algorithm: finds the distance between every pair of vertices,
+    /// allowing negative weights, or returns a negative cycle if one exists.
+    /// Bellman-Ford from a virtual source computes potentials that make every
+    /// weight non-negative, after which Dijkstra runs from each vertex.
+    /// O(VE log V), which beats Floyd-Warshall on sparse graphs.
+    pub fn johnson(&self, weights: &[i64]) -> Result<Vec<Vec<Option<i64>>>, NegativeCycle> {
+        assert_eq!(self.num_e(), weights.len());
+        let (n, tail) = (self.num_v(), self.tails());
+        let mut augmented = Graph::new(n + 1, self.num_e() + n);
+        let mut aug_weights = weights.to_vec();
+        for (&u, &v) in tail.iter().zip(&self.endp) {
+            augmented.add_edge(u, v);
+        }
+        for u in 0..n {
+            augmented.add_edge(n, u);
+            aug_weights.push(0);
+        }
+        let pot = augmented
+            .bellman_ford(&aug_weights, n)?
+            .into_iter()
+            .map(Option::unwrap)
+            .collect::<Vec<_>>();
+
+        let reweighted = (0..self.num_e())
+            .map(|e| weights[e] + pot[tail[e]] - pot[self.endp[e]])
+            .collect::<Vec<_>>();
+        Ok((0..n)
+            .map(|u| {
+                let dist = self.dijkstra(&reweighted, u).0;
+                (0..n)
+                    .map(|v| dist[v].map(|d| d - pot[u] + pot[v]))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Finds the greatest distance between any ordered pair of vertices, using
+    /// johnson(). Returns None if some vertex can't reach another, or if there
+    /// are no vertices. O(VE log V).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the graph has a negative cycle.
+    pub fn weighted_diameter(&self, weights: &[i64]) -> Option<i64> {
+        let dist = self.johnson(weights).expect("graph has a negative cycle");
+        dist.into_iter()
+            .flatten()
+            .collect::<Option<Vec<_>>>()?
+            .into_iter()
+            .max()
+    }
+
+    /// Builds the matrix of edge weights between every pair of vertices, taking
+    /// the minimum over parallel edges, with 0 on the diagonal and None where
+    /// there is no edge. This is the input format of floyd_warshall().
+    pub fn to_cost_matrix(&self, weights: &[i64]) -> Vec<Vec<Option<i64>>> {
+        assert_eq!(self.num_e(), weights.len());
+        let mut dist = vec![vec![None; self.num_v()]; self.num_v()];
+        for (u, row) in dist.iter_mut().enumerate() {
+            row[u] = Some(0);
+            for (e, v) in self.adj_list(u) {
+                if row[v].is_none_or(|d| d > weights[e]) {
+                    row[v] = Some(weights[e]);
+                }
+            }
+        }
+        dist
+    }
+
+    /// Walks a predecessor edge array, such as the one produced by dijkstra(),
+    /// back from target to recover the sequence of edges leading to it. The
+    /// result is empty if target is the source or unreachable.
+    pub fn path_from_pred(&self, pred: &[Option<usize>], target: usize) -> Vec<usize> {
+        Self::walk_pred(&self.tails(), pred, target)
+    }
+
+    // Helper function used by path_from_pred and k_shortest_paths. Takes the
+    // edge tails, so that callers walking many paths recover them only once.
+    fn walk_pred(tail: &[usize], pred: &[Option<usize>], target: usize) -> Vec<usize> {
+        let mut edges = Vec::new();
+        let mut u = target;
+        while let Some(e) = pred[u] {
+            edges.push(e);
+            u = tail[e];
+        }
+        edges.reverse();
+        edges
+    }
+}
+
+/// Floyd-Warshall algorithm on a dense matrix of edge weights, where None
+/// represents infinity: replaces each entry by the shortest path distance
+/// between the corresponding pair of vertices. O(V^3).
+pub fn floyd_warshall(dist: &mut [Vec<Option<i64>>]) {
+    for k in 0..dist.len() {
+        let row_k = dist[k].clone();
+        for row_u in dist.iter_mut() {
+            if let Some(dist_uk) = row_u[k] {
+                for (dist_uv, &dist_kv) in row_u.iter_mut().zip(&row_k) {
+                    if let Some(dist_kv) = dist_kv {
+                        if dist_uv.is_none_or(|d| d > dist_uk + dist_kv) {
+                            *dist_uv = Some(dist_uk + dist_kv);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Given an all-pairs distance matrix, such as the one produced by
+/// floyd_warshall(), determines whether some vertex lies on a negative cycle.
+pub fn has_negative_cycle(dist: &[Vec<Option<i64>>]) -> bool {
+    (0..dist.len()).any(|u| dist[u][u] < Some(0))
+}
+
+/// Builds the tree formed by a predecessor edge array, such as the one
+/// produced by dijkstra() or bfs(), as a graph on the same vertices with one
+/// edge from each vertex's predecessor to it, in order of vertex id. Also
+/// returns the original id of each tree edge. O(V + E).
+///
+/// # Panics
+///
+/// Panics if some predecessor edge doesn't point to its vertex, or if the
+/// predecessor edges contain a cycle.
+pub fn shortest_path_tree(parent_edge: &[Option<usize>], original: &Graph) -> (Graph, Vec<usize>) {
+    assert_eq!(original.num_v(), parent_edge.len());
+    let tail = original.tails();
+    let n = original.num_v();
+
+    // Walk up from each vertex until reaching a root or a vertex known to
+    // lead to one; state is 0 if unvisited, 1 if on the current walk, and 2
+    // if known to lead to a root.
+    let mut state = vec![0u8; n];
+    for v in 0..n {
+        let mut u = v;
+        while state[u] == 0 {
+            state[u] = 1;
+            match parent_edge[u] {
+                Some(e) => {
+                    assert_eq!(
+                        original.endp[e], u,
+                        "predecessor edge {} doesn't enter {}",
+                        e, u
+                    );
+                    u = tail[e];
+                }
+                None => break,
+            }
+        }
+        assert!(
+            state[u] != 1 || parent_edge[u].is_none(),
+            "predecessor edges form a cycle"
+        );
+        let mut u = v;
+        while state[u] == 1 {
+            state[u] = 2;
+            if let Some(e) = parent_edge[u] {
+                u = tail[e];
+            }
+        }
+    }
+
+    let edges = parent_edge.iter().flatten().copied().collect::<Vec<_>>();
+    let mut tree = Graph::new(n, edges.len());
+    for &e in &edges {
+        tree.add_edge(tail[e], original.endp[e]);
+    }
+    (tree, edges)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::xorshift;
+
+    #[test]
+    fn test_bfs() {
+        let mut graph = Graph::new(4, 6);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+
+        assert_eq!(graph.bfs(0).0, vec![Some(0), Some(1), Some(1), None]);
+
+        let mut graph = Graph::new(4, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 0);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+
+        let (dist, pred) = graph.bfs(0);
+        assert_eq!(dist, vec![Some(0), Some(1), Some(2), None]);
+        assert_eq!(pred, vec![None, Some(0), Some(2), None]);
+        assert_eq!(graph.path_from_pred(&pred, 2), vec![0, 2]);
+        assert_eq!(graph.bfs(2).0, vec![Some(2), Some(1), Some(0), None]);
+    }
+
+    #[test]
+    fn test_farthest_vertex() {
+        let mut path = Graph::new(6, 10);
+        for u in 0..5 {
+            path.add_undirected_edge(u, u + 1);
+        }
+        assert_eq!(path.farthest_vertex(0), (5, 5));
+        assert_eq!(path.farthest_vertex(5), (0, 5));
+        assert_eq!(path.farthest_vertex(2), (5, 3));
+
+        // Double BFS on a spider whose longest legs have lengths 2 and 3.
+        let mut tree = Graph::new(7, 12);
+        for &(u, v) in &[(0, 1), (1, 2), (0, 3), (3, 4), (4, 5), (0, 6)] {
+            tree.add_undirected_edge(u, v);
+        }
+        let (end, _) = tree.farthest_vertex(6);
+        assert_eq!(tree.farthest_vertex(end), (2, 5));
+    }
+
+    #[test]
+    fn test_approx_diameter() {
+        // A caterpillar whose longest path 5-2-1-3-4-6 hides behind vertex 0.
+        let mut tree = Graph::new(7, 12);
+        for &(u, v) in &[(0, 1), (1, 2), (1, 3), (3, 4), (2, 5), (4, 6)] {
+            tree.add_undirected_edge(u, v);
+        }
+        let exact = (0..7).map(|u| tree.farthest_vertex(u).1).max();
+        assert_eq!(Some(tree.approx_diameter()), exact);
+        assert_eq!(tree.approx_diameter(), 5);
+        assert_eq!(Graph::new(0, 0).approx_diameter(), 0);
+    }
+
+    #[test]
+    fn test_all_pairs_bfs_parallel() {
+        let mut graph = Graph::new(9, 14);
+        for u in 0..7 {
+            graph.add_edge(u, u + 1);
+        }
+        graph.add_edge(7, 3);
+        graph.add_undirected_edge(0, 5);
+        graph.add_edge(8, 2);
+
+        let dist = graph.all_pairs_bfs();
+        assert_eq!(dist[0][7], Some(3));
+        assert_eq!(dist[7][2], Some(6));
+        assert_eq!(dist[2][8], None);
+        assert_eq!(dist[8][3], Some(2));
+        assert_eq!(graph.all_pairs_bfs_parallel(), dist);
+        for num_threads in 1..12 {
+            assert_eq!(graph.all_pairs_bfs_threads(num_threads), dist);
+        }
+        assert!(Graph::new(0, 0).all_pairs_bfs_parallel().is_empty());
+    }
+
+    #[test]
+    fn test_multi_source_bfs() {
+        let mut path = Graph::new(7, 12);
+        for u in 0..6 {
+            path.add_undirected_edge(u, u + 1);
+        }
+        let dist = path.multi_source_bfs(&[0, 5]);
+        assert_eq!(
+            dist,
+            vec![
+                Some(0),
+                Some(1),
+                Some(2),
+                Some(2),
+                Some(1),
+                Some(0),
+                Some(1)
+            ]
+        );
+        assert_eq!(path.multi_source_bfs(&[3, 3]), path.bfs(3).0);
+        assert_eq!(path.multi_source_bfs(&[]), vec![None; 7]);
+    }
+
+    #[test]
+    fn test_min_xor_path() {
+        // The direct edge 0-2 has XOR 6, but going around the triangle 0-1-2
+        // instead gives 5 ^ 1 = 4. Joining the edge 2-3 to either gives 5 or 7.
+        let mut graph = Graph::new(5, 8);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        let weights = [6, 6, 5, 5, 1, 1, 3, 3];
+        assert_eq!(graph.min_xor_path(&weights, 0, 2), Some(4));
+        assert_eq!(graph.min_xor_path(&weights, 0, 3), Some(5));
+        assert_eq!(graph.min_xor_path(&weights, 3, 3), Some(0));
+        assert_eq!(graph.min_xor_path(&weights, 0, 4), None);
+
+        // Compare with a search over (vertex, XOR) states, which explores all
+        // walks since weights are below 16.
+        let mut rand = xorshift(0x7a3c_91e5_03bd_2f48);
+        for _ in 0..20 {
+            let mut graph = Graph::new(8, 20);
+            let mut weights = vec![];
+            for _ in 0..10 {
+                graph.add_undirected_edge(rand() as usize % 8, rand() as usize % 8);
+                let w = rand() % 16;
+                weights.extend([w, w]);
+            }
+            let mut seen = [[false; 16]; 8];
+            let mut stack = vec![(0, 0)];
+            seen[0][0] = true;
+            while let Some((u, x)) = stack.pop() {
+                for (e, v) in graph.adj_list(u) {
+                    let y = x ^ weights[e] as usize;
+                    if !seen[v][y] {
+                        seen[v][y] = true;
+                        stack.push((v, y));
+                    }
+                }
+            }
+            for (t, seen_t) in seen.iter().enumerate() {
+                let expected = seen_t.iter().position(|&b| b).map(|x| x as u64);
+                assert_eq!(graph.min_xor_path(&weights, 0, t), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zero_one_bfs() {
+        let mut graph = Graph::new(5, 5);
+        graph.add_edge(0, 3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 4);
+        let weights = [1, 0, 0, 0, 1];
+
+        // The 3-hop route to vertex 3 is cheaper than the direct edge.
+        assert_eq!(graph.bfs(0).0[3], Some(1));
+        assert_eq!(
+            graph.zero_one_bfs(&weights, 0),
+            vec![Some(0), Some(0), Some(0), Some(0), Some(1)]
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_one_bfs_bad_weight() {
+        let mut graph = Graph::new(2, 1);
+        graph.add_edge(0, 1);
+        graph.zero_one_bfs(&[2], 0);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let mut graph = Graph::new(5, 5);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        graph.add_edge(0, 3);
+        let weights = [2, 1, 2, 3, 5];
+
+        let (dist, pred) = graph.dijkstra(&weights, 0);
+        assert_eq!(dist, vec![Some(0), Some(2), Some(1), Some(4), None]);
+        assert_eq!(pred[4], None);
+
+        // Two shortest paths reach vertex 3; either one may be reported.
+        let path = graph.path_from_pred(&pred, 3);
+        let cost = path.iter().map(|&e| weights[e]).sum::<u64>();
+        assert!(path == vec![0, 2] || path == vec![1, 3]);
+        assert_eq!(cost, 4);
+        assert!(graph.path_from_pred(&pred, 0).is_empty());
+        assert!(graph.path_from_pred(&pred, 4).is_empty());
+    }
+
+    #[test]
+    fn test_dijkstra_signed() {
+        let mut graph = Graph::new(4, 5);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 0);
+        graph.add_edge(3, 0);
+        let weights: [i64; 5] = [4, 3, 9, 1, 2];
+
+        let (dist, pred) = graph.dijkstra(&weights, 0);
+        assert_eq!(dist, vec![Some(0), Some(4), Some(7), None]);
+        assert_eq!(pred, vec![None, Some(0), Some(1), None]);
+    }
+
+    #[test]
+    #[should_panic(expected = "nonnegative weights")]
+    fn test_dijkstra_negative_weight() {
+        // A negative undirected edge is already a negative cycle.
+        let mut graph = Graph::new(2, 2);
+        graph.add_undirected_edge(0, 1);
+        graph.dijkstra(&[-1i64, -1], 0);
+    }
+
+    #[test]
+    fn test_weighted_diameter() {
+        let mut path = Graph::new(5, 8);
+        for u in 0..4 {
+            path.add_undirected_edge(u, u + 1);
+        }
+        let weights = [3, 3, 1, 1, 4, 4, 1, 1];
+        assert_eq!(path.weighted_diameter(&weights), Some(9));
+
+        // A shortcut from 0 to 4 brings 1 and 3, among others, within 5.
+        path.add_undirected_edge(0, 4);
+        let weights = [3, 3, 1, 1, 4, 4, 1, 1, 2, 2];
+        assert_eq!(path.weighted_diameter(&weights), Some(5));
+
+        let mut directed = Graph::new(2, 1);
+        directed.add_edge(0, 1);
+        assert_eq!(directed.weighted_diameter(&[5]), None);
+        assert_eq!(Graph::new(1, 0).weighted_diameter(&[]), Some(0));
+    }
+
+    #[test]
+    fn test_shortest_path_tree() {
+        let mut graph = Graph::new(6, 8);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 1);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 4);
+        graph.add_edge(4, 0);
+        graph.add_edge(5, 4);
+        let weights = [5u64, 1, 2, 3, 7, 1, 1, 1];
+
+        let (dist, pred) = graph.dijkstra(&weights, 0);
+        let (tree, orig) = shortest_path_tree(&pred, &graph);
+        assert_eq!(tree.num_v(), 6);
+        assert_eq!(tree.num_e(), 4);
+
+        // Every vertex has at most one parent, so DFS suffices for distances.
+        let mut tree_dist = vec![None; 6];
+        let mut stack = vec![0];
+        tree_dist[0] = Some(0);
+        while let Some(u) = stack.pop() {
+            for (e, v) in tree.adj_list(u) {
+                tree_dist[v] = tree_dist[u].map(|d| d + weights[orig[e]]);
+                stack.push(v);
+            }
+        }
+        assert_eq!(tree_dist, dist);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_shortest_path_tree_cycle() {
+        let mut graph = Graph::new(3, 3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        shortest_path_tree(&[None, Some(2), Some(1)], &graph);
+    }
+
+    #[test]
+    fn test_dijkstra_path_edges() {
+        let mut graph = Graph::new(5, 7);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 1);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 0);
+        graph.add_edge(4, 3);
+        let weights = [6, 2, 3, 1, 5, 1, 1];
+
+        let dist = graph.dijkstra(&weights, 0).0;
+        for (v, &dist_v) in dist.iter().enumerate().take(4) {
+            let path = graph.dijkstra_path_edges(&weights, 0, v).unwrap();
+            let cost = path.iter().map(|&e| weights[e]).sum::<i64>();
+            assert_eq!(Some(cost), dist_v);
+        }
+        assert_eq!(
+            graph.dijkstra_path_edges(&weights, 0, 3),
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(graph.dijkstra_path_edges(&weights, 0, 0), Some(vec![]));
+        assert_eq!(graph.dijkstra_path_edges(&weights, 0, 4), None);
+    }
+
+    #[test]
+    fn test_shortest_path_parity() {
+        // The direct edge 0-2 is shortest but odd, so the even walk to 2 goes
+        // through 1 instead. Odd walks back to 0 go around the triangle.
+        let mut graph = Graph::new(4, 6);
+        graph.add_undirected_edge(0, 2);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        let weights = [1, 1, 3, 3, 4, 4];
+
+        assert_eq!(
+            graph.shortest_path_parity(&weights, 0, false),
+            vec![Some(8), Some(3), Some(1), None]
+        );
+        assert_eq!(
+            graph.shortest_path_parity(&weights, 0, true),
+            vec![Some(0), Some(5), Some(7), None]
+        );
+    }
+
+    #[test]
+    fn test_shortest_path_dag() {
+        // Routes 0-1-3 and 0-2-3 both cost 4; the direct edge 0-3 costs 5.
+        let mut graph = Graph::new(5, 6);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 3);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(0, 3);
+        graph.add_edge(4, 3);
+        let weights = [1, 3, 2, 2, 5, 0];
+
+        let dag = graph.shortest_path_dag(&weights, 0);
+        let edges = (0..5)
+            .flat_map(|u| dag.adj_list(u).map(move |(_, v)| (u, v)))
+            .collect::<Vec<_>>();
+        assert_eq!(edges, vec![(0, 2), (0, 1), (1, 3), (2, 3)]);
+        assert_eq!(dag.dag_count_paths(0, 3, None), Some(2));
+    }
+
+    #[test]
+    fn test_count_shortest_paths() {
+        // Three diamonds in series, plus a longer bypass from 0 to 6.
+        let mut graph = Graph::new(11, 13);
+        for &base in &[0, 3, 6] {
+            graph.add_edge(base, base + 1);
+            graph.add_edge(base, base + 2);
+            graph.add_edge(base + 1, base + 3);
+            graph.add_edge(base + 2, base + 3);
+        }
+        graph.add_edge(0, 6);
+        let mut weights = vec![1; 12];
+        weights.push(5);
+
+        let (dist, count) = graph.count_shortest_paths(&weights, 0, 1_000_000_007);
+        assert_eq!(dist[9], Some(6));
+        assert_eq!(dist[10], None);
+        assert_eq!(count[3], 2);
+        assert_eq!(count[6], 4);
+        assert_eq!(count[9], 8);
+        assert_eq!(count[10], 0);
+
+        let (_, count) = graph.count_shortest_paths(&weights, 0, 3);
+        assert_eq!(count[6], 1);
+        assert_eq!(count[9], 2);
+
+        let (_, count) = graph.count_shortest_paths(&weights, 0, 1);
+        assert!(count.iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn test_count_shortest_paths_large_modulus() {
+        // 64 diamonds in series have 2^64 shortest paths, which is 1 modulo
+        // 2^64 - 1. Midway through the last diamond, the count is 2^63.
+        let mut graph = Graph::new(193, 256);
+        for base in (0..192).step_by(3) {
+            graph.add_edge(base, base + 1);
+            graph.add_edge(base, base + 2);
+            graph.add_edge(base + 1, base + 3);
+            graph.add_edge(base + 2, base + 3);
+        }
+        let (dist, count) = graph.count_shortest_paths(&[1; 256], 0, u64::MAX);
+        assert_eq!(dist[192], Some(128));
+        assert_eq!(count[189], 1 << 63);
+        assert_eq!(count[192], 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "modulus must be positive")]
+    fn test_count_shortest_paths_zero_modulus() {
+        Graph::new(1, 0).count_shortest_paths(&[], 0, 0);
+    }
+
+    #[test]
+    fn test_k_shortest_paths() {
+        let mut graph = Graph::new(6, 9);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 1);
+        graph.add_edge(2, 3);
+        graph.add_edge(2, 4);
+        graph.add_edge(3, 4);
+        graph.add_edge(3, 5);
+        graph.add_edge(4, 5);
+        let weights = [3, 2, 4, 1, 2, 3, 2, 1, 2];
+
+        let paths = graph.k_shortest_paths(&weights, 0, 5, 3);
+        assert_eq!(paths.len(), 3);
+        assert_eq!(paths[0], (5, vec![1, 4, 7]));
+        assert_eq!(paths[1], (7, vec![1, 5, 8]));
+        assert_eq!(paths[2].0, 8);
+
+        // There are only 7 simple paths in total, 3 of them tied at weight 8.
+        let paths = graph.k_shortest_paths(&weights, 0, 5, 10);
+        let costs = paths.iter().map(|&(c, _)| c).collect::<Vec<_>>();
+        assert_eq!(costs, vec![5, 7, 8, 8, 8, 11, 11]);
+        for (i, (cost, path)) in paths.iter().enumerate() {
+            assert_eq!(path.iter().map(|&e| weights[e]).sum::<u64>(), *cost);
+            assert!(paths[..i].iter().all(|(_, other)| other != path));
+        }
+
+        assert_eq!(graph.k_shortest_paths(&weights, 5, 0, 3), vec![]);
+        assert_eq!(graph.k_shortest_paths(&weights, 0, 0, 3), vec![(0, vec![])]);
+    }
+
+    #[test]
+    fn test_a_star() {
+        // A 5x5 grid, where vertex 5 * y + x sits at coordinates (x, y).
+        let mut graph = Graph::new(26, 80);
+        for y in 0..5 {
+            for x in 0..5 {
+                if x + 1 < 5 {
+                    graph.add_undirected_edge(5 * y + x, 5 * y + x + 1);
+                }
+                if y + 1 < 5 {
+                    graph.add_undirected_edge(5 * y + x, 5 * y + x + 5);
+                }
+            }
+        }
+        let weights = vec![1; graph.num_e()];
+        let (source, target) = (10, 14);
+        // Manhattan distance is a consistent heuristic on the grid.
+        let manhattan = |u: usize| {
+            let (x, y) = (u % 5, u / 5);
+            (4 - x + y.abs_diff(2)) as u64
+        };
+
+        let (informed, informed_count) = graph.a_star(&weights, source, target, manhattan);
+        let (blind, blind_count) = graph.a_star(&weights, source, target, |_| 0);
+        let (dist, path) = informed.unwrap();
+        assert_eq!(dist, 4);
+        assert_eq!(path.len(), 4);
+        assert_eq!(blind.unwrap().0, 4);
+        assert!(informed_count < blind_count);
+
+        assert_eq!(graph.a_star(&weights, source, 25, |_| 0).0, None);
+    }
+
+    #[test]
+    fn test_bellman_ford() {
+        let mut graph = Graph::new(5, 4);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 1);
+        graph.add_edge(1, 3);
+        let weights = [4, 1, -2, 1];
+
+        let dist = graph.bellman_ford(&weights, 0);
+        assert_eq!(dist, Ok(vec![Some(0), Some(-1), Some(1), Some(0), None]));
+    }
+
+    #[test]
+    fn test_bellman_ford_negative_cycle() {
+        let mut graph = Graph::new(5, 5);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(0, 3);
+        graph.add_edge(3, 4);
+        graph.add_edge(4, 3);
+        let weights = [1, 1, 1, -2, 1];
+
+        // The cycle 3 -> 4 -> 3 is off the path to 2, but still reachable.
+        let NegativeCycle(cycle) = graph.bellman_ford(&weights, 0).unwrap_err();
+        assert!(cycle == vec![3, 4] || cycle == vec![4, 3]);
+        assert!(graph.bellman_ford(&weights, 1).is_ok());
+    }
+
+    #[test]
+    fn test_spfa() {
+        let mut rand = xorshift(0x1234_5678_9abc_def1);
+        let mut num_cycles = 0;
+        for _ in 0..50 {
+            let mut graph = Graph::new(15, 40);
+            let mut weights = vec![];
+            for _ in 0..40 {
+                let (u, v) = (rand() as usize % 15, rand() as usize % 15);
+                graph.add_edge(u, v);
+                weights.push(rand() as i64 % 21 - 3);
+            }
+            match graph.bellman_ford(&weights, 0) {
+                Ok(dist) => assert_eq!(graph.spfa(&weights, 0), Ok(dist)),
+                Err(_) => {
+                    assert!(graph.spfa(&weights, 0).is_err());
+                    num_cycles += 1;
+                }
+            }
+        }
+        assert!(0 < num_cycles && num_cycles < 50);
+    }
+
+    #[test]
+    fn test_spfa_negative_cycle() {
+        // A long path 0 -> ... -> 49 leads to the negative cycle 49 -> 50 -> 49.
+        let mut graph = Graph::new(51, 51);
+        for u in 0..50 {
+            graph.add_edge(u, u + 1);
+        }
+        graph.add_edge(50, 49);
+        let mut weights = vec![1; 50];
+        weights.push(-2);
+
+        let v = graph.spfa(&weights, 0).unwrap_err();
+        assert!(v == 49 || v == 50);
+
+        weights[50] = -1;
+        let dist = graph.spfa(&weights, 0).unwrap();
+        assert_eq!(dist[50], Some(50));
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        let mut graph = Graph::new(5, 10);
+        graph.add_edge(0, 1);
+        graph.add_edge(0, 2);
+        graph.add_edge(0, 4);
+        graph.add_edge(1, 3);
+        graph.add_edge(1, 4);
+        graph.add_edge(2, 1);
+        graph.add_edge(3, 0);
+        graph.add_edge(3, 2);
+        graph.add_edge(4, 3);
+        graph.add_edge(4, 3);
+        let weights = [3, 8, -4, 1, 7, 4, 2, -5, 9, 6];
+
+        let (dist, next) = graph.floyd_warshall(&weights);
+        let expected = [
+            [0, 1, -3, 2, -4],
+            [3, 0, -4, 1, -1],
+            [7, 4, 0, 5, 3],
+            [2, -1, -5, 0, -2],
+            [8, 5, 1, 6, 0],
+        ];
+        for u in 0..5 {
+            for v in 0..5 {
+                assert_eq!(dist[u][v], Some(expected[u][v]));
+            }
+        }
+        assert!(!has_negative_cycle(&dist));
+
+        let mut path = vec![0];
+        while path[path.len() - 1] != 2 {
+            path.push(next[path[path.len() - 1]][2].unwrap());
+        }
+        assert_eq!(path, vec![0, 4, 3, 2]);
+    }
+
+    #[test]
+    fn test_floyd_warshall_matrix() {
+        let mut graph = Graph::new(4, 5);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(0, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 1);
+        let weights = [1, 2, 10, 3, 1];
+
+        let mut dist = graph.to_cost_matrix(&weights);
+        assert_eq!(dist[0], vec![Some(0), Some(1), Some(10), None]);
+        floyd_warshall(&mut dist);
+
+        // The direct edge 0 -> 2 is longer than the path through 1.
+        assert_eq!(dist[0], vec![Some(0), Some(1), Some(3), Some(6)]);
+        assert_eq!(dist[1], vec![None, Some(0), Some(2), Some(5)]);
+        assert_eq!(dist[2], vec![None, Some(4), Some(0), Some(3)]);
+        assert_eq!(dist[3], vec![None, Some(1), Some(3), Some(0)]);
+        assert_eq!(dist, graph.floyd_warshall(&weights).0);
+    }
+
+    #[test]
+    fn test_shortest_path_bounded() {
+        let mut graph = Graph::new(4, 5);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 3);
+        graph.add_edge(0, 3);
+        graph.add_edge(0, 2);
+        let weights = [1, -2, 1, 5, 2];
+
+        assert_eq!(graph.shortest_path_bounded(&weights, 0, 3, 0), None);
+        assert_eq!(graph.shortest_path_bounded(&weights, 0, 0, 0), Some(0));
+        assert_eq!(graph.shortest_path_bounded(&weights, 0, 3, 1), Some(5));
+        assert_eq!(graph.shortest_path_bounded(&weights, 0, 3, 2), Some(3));
+        assert_eq!(graph.shortest_path_bounded(&weights, 0, 3, 3), Some(0));
+        assert_eq!(graph.shortest_path_bounded(&weights, 0, 3, 9), Some(0));
+    }
+
+    #[test]
+    fn test_johnson() {
+        // Forward edges may be negative, but backward edges are heavy enough
+        // that every cycle has positive weight.
+        let mut rand = xorshift(0x2545_f491_4f6c_dd1d);
+        let mut graph = Graph::new(20, 60);
+        let mut weights = vec![];
+        for _ in 0..60 {
+            let (u, v) = (rand() as usize % 20, rand() as usize % 20);
+            graph.add_edge(u, v);
+            weights.push(if u < v { rand() as i64 % 11 - 5 } else { 200 });
+        }
+
+        let mut dist = graph.to_cost_matrix(&weights);
+        floyd_warshall(&mut dist);
+        assert!(weights.iter().any(|&w| w < 0));
+        assert_eq!(graph.johnson(&weights), Ok(dist));
+
+        let mut graph = Graph::new(3, 3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        assert!(graph.johnson(&[1, 2, -3]).is_err());
+    }
+
+    #[test]
+    fn test_floyd_warshall_negative_cycle() {
+        let mut graph = Graph::new(3, 3);
+        graph.add_edge(0, 1);
+        graph.add_edge(1, 2);
+        graph.add_edge(2, 1);
+        let weights = [1, 2, -3];
+
+        let (dist, _) = graph.floyd_warshall(&weights);
+        assert_eq!(dist[2][0], None);
+        assert!(has_negative_cycle(&dist));
+    }
+}
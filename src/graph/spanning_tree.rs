@@ -0,0 +1,490 @@
+//! Spanning tree analysis of undirected graphs. As in min_spanning_tree(),
+//! undirected edge e consists of the directed edges 2e and 2e+1.
+use super::{DisjointSets, Graph};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Reasons why a claimed minimum spanning tree may be rejected.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MstError {
+    /// The edges don't form a spanning tree.
+    NotSpanningTree,
+    /// The given non-tree edge is lighter than some edge on the tree path
+    /// between its endpoints, which it should replace.
+    NotMinimal(usize),
+}
+
+/// A spanning forest, each of whose trees is rooted at its smallest vertex,
+/// with binary lifting tables that support queries on tree paths in O(log V).
+struct RootedTree {
+    depth: Vec<usize>,
+    /// The tree edge from each vertex to its parent, or None at the roots.
+    parent_edge: Vec<Option<usize>>,
+    /// up[j][v] is the 2^j-th ancestor of v, or the root if there's none.
+    up: Vec<Vec<usize>>,
+    /// max_w[j][v] is the maximum weight on the path from v to up[j][v]. Only
+    /// filled in by with_weights().
+    max_w: Vec<Vec<i64>>,
+}
+
+impl RootedTree {
+    fn new(graph: &Graph, tree_edges: &[usize]) -> Self {
+        graph.assert_undirected();
+        let n = graph.num_v();
+        let mut tree = Graph::new(n, 2 * tree_edges.len());
+        for &e in tree_edges {
+            tree.add_undirected_edge(graph.endp[2 * e + 1], graph.endp[2 * e]);
+        }
+        let mut depth = vec![0; n];
+        let mut parent_edge = vec![None; n];
+        let mut up = vec![(0..n).collect::<Vec<_>>()];
+        let mut visited = vec![false; n];
+        for root in 0..n {
+            if visited[root] {
+                continue;
+            }
+            let mut stack = vec![root];
+            visited[root] = true;
+            while let Some(u) = stack.pop() {
+                for (e, v) in tree.adj_list(u) {
+                    if !visited[v] {
+                        visited[v] = true;
+                        depth[v] = depth[u] + 1;
+                        parent_edge[v] = Some(tree_edges[e / 2]);
+                        up[0][v] = u;
+                        stack.push(v);
+                    }
+                }
+            }
+        }
+        while 1 << up.len() < n {
+            let prev = &up[up.len() - 1];
+            let next = prev.iter().map(|&v| prev[v]).collect();
+            up.push(next);
+        }
+        Self {
+            depth,
+            parent_edge,
+            up,
+            max_w: vec![],
+        }
+    }
+
+    fn with_weights(mut self, weights: &[i64]) -> Self {
+        let base = self
+            .parent_edge
+            .iter()
+            .map(|pe| pe.map_or(i64::MIN, |e| weights[e]))
+            .collect();
+        self.max_w = vec![base];
+        for j in 1..self.up.len() {
+            let (prev_up, prev_max) = (&self.up[j - 1], &self.max_w[j - 1]);
+            let next = (0..prev_up.len())
+                .map(|v| prev_max[v].max(prev_max[prev_up[v]]))
+                .collect();
+            self.max_w.push(next);
+        }
+        self
+    }
+
+    /// Finds the lowest common ancestor of u and v, which must share a tree.
+    fn lca(&self, mut u: usize, mut v: usize) -> usize {
+        if self.depth[u] < self.depth[v] {
+            ::std::mem::swap(&mut u, &mut v);
+        }
+        for j in (0..self.up.len()).rev() {
+            if self.depth[u] - self.depth[v] >= 1 << j {
+                u = self.up[j][u];
+            }
+        }
+        if u == v {
+            return u;
+        }
+        for j in (0..self.up.len()).rev() {
+            if self.up[j][u] != self.up[j][v] {
+                u = self.up[j][u];
+                v = self.up[j][v];
+            }
+        }
+        self.up[0][u]
+    }
+
+    /// Lists the tree edges on the path from u up to its ancestor a.
+    fn path_up(&self, mut u: usize, a: usize) -> Vec<usize> {
+        let mut edges = vec![];
+        while u != a {
+            edges.push(self.parent_edge[u].unwrap());
+            u = self.up[0][u];
+        }
+        edges
+    }
+
+    /// Finds the maximum weight on the tree path between u and v.
+    fn path_max(&self, mut u: usize, mut v: usize) -> i64 {
+        let mut result = i64::MIN;
+        if self.depth[u] < self.depth[v] {
+            ::std::mem::swap(&mut u, &mut v);
+        }
+        for j in (0..self.up.len()).rev() {
+            if self.depth[u] - self.depth[v] >= 1 << j {
+                result = result.max(self.max_w[j][u]);
+                u = self.up[j][u];
+            }
+        }
+        if u == v {
+            return result;
+        }
+        for j in (0..self.up.len()).rev() {
+            if self.up[j][u] != self.up[j][v] {
+                result = result.max(self.max_w[j][u]).max(self.max_w[j][v]);
+                u = self.up[j][u];
+                v = self.up[j][v];
+            }
+        }
+        result.max(self.max_w[0][u]).max(self.max_w[0][v])
+    }
+}
+
+impl Graph {
+    /// Verifies that tree_edges form a minimum spanning tree, without running
+    /// Kruskal's algorithm: a spanning tree is minimum iff every non-tree edge
+    /// weighs at least as much as every edge on the tree path between its
+    /// endpoints. O((V + E) log V).
+    pub fn verify_mst(&self, weights: &[i64], tree_edges: &[usize]) -> Result<(), MstError> {
+        assert_eq!(self.num_e(), 2 * weights.len());
+        let n = self.num_v();
+        if n == 0 {
+            return Ok(());
+        }
+        let mut components = DisjointSets::new(n);
+        // V - 1 edges without a cycle must connect all V vertices.
+        if tree_edges.len() + 1 != n
+            || !tree_edges
+                .iter()
+                .all(|&e| components.merge(self.endp[2 * e], self.endp[2 * e + 1]))
+        {
+            return Err(MstError::NotSpanningTree);
+        }
+
+        let tree = RootedTree::new(self, tree_edges).with_weights(weights);
+        let mut in_tree = vec![false; weights.len()];
+        for &e in tree_edges {
+            in_tree[e] = true;
+        }
+        match (0..weights.len()).find(|&e| {
+            !in_tree[e] && weights[e] < tree.path_max(self.endp[2 * e], self.endp[2 * e + 1])
+        }) {
+            Some(e) => Err(MstError::NotMinimal(e)),
+            None => Ok(()),
+        }
+    }
+
+    /// Counts the minimum spanning forests of an undirected graph. Kruskal's
+    /// algorithm may process each group of equal-weight edges in any order;
+    /// the choices within a group are the spanning forests of the multigraph
+    /// obtained by contracting all lighter edges, counted by Kirchhoff's
+    /// matrix-tree theorem on each of its components. O(V^3 + E log E).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the count overflows a u64.
+    pub fn count_mst(&self, weights: &[i64]) -> u64 {
+        assert_eq!(self.num_e(), 2 * weights.len());
+        self.assert_undirected();
+        let mut edges = (0..weights.len()).collect::<Vec<_>>();
+        edges.sort_unstable_by_key(|&e| weights[e]);
+
+        let mut components = DisjointSets::new(self.num_v());
+        let mut count = 1u64;
+        for group in edges.chunk_by(|&e, &f| weights[e] == weights[f]) {
+            let contracted = group
+                .iter()
+                .map(|&e| {
+                    let u = components.find(self.endp[2 * e]);
+                    (u, components.find(self.endp[2 * e + 1]))
+                })
+                .filter(|&(u, v)| u != v)
+                .collect::<Vec<_>>();
+            // Split the contracted multigraph into components, and give each
+            // vertex an index within its component.
+            let mut pieces = DisjointSets::new(self.num_v());
+            for &(u, v) in &contracted {
+                pieces.merge(u, v);
+            }
+            let mut index = vec![None; self.num_v()];
+            let mut piece_size = vec![0; self.num_v()];
+            for &(u, v) in &contracted {
+                for w in [u, v] {
+                    if index[w].is_none() {
+                        let root = pieces.find(w);
+                        index[w] = Some(piece_size[root]);
+                        piece_size[root] += 1;
+                    }
+                }
+            }
+            // Build the Laplacian of each component, minus its first row and
+            // column, and multiply their determinants.
+            let mut laplacians = HashMap::new();
+            for &(u, v) in &contracted {
+                let root = pieces.find(u);
+                let size = piece_size[root] - 1;
+                let lap = laplacians
+                    .entry(root)
+                    .or_insert_with(|| vec![vec![0i128; size]; size]);
+                let (i, j) = (index[u].unwrap(), index[v].unwrap());
+                if i > 0 {
+                    lap[i - 1][i - 1] += 1;
+                }
+                if j > 0 {
+                    lap[j - 1][j - 1] += 1;
+                }
+                if i > 0 && j > 0 {
+                    lap[i - 1][j - 1] -= 1;
+                    lap[j - 1][i - 1] -= 1;
+                }
+            }
+            for lap in laplacians.into_values() {
+                let num_trees = u64::try_from(det_bareiss(lap)).expect("MST count overflow");
+                count = count.checked_mul(num_trees).expect("MST count overflow");
+            }
+            for (u, v) in contracted {
+                components.merge(u, v);
+            }
+        }
+        count
+    }
+
+    /// For each edge not in the given spanning forest, in increasing order of
+    /// id, finds the fundamental cycle that it forms with the forest: a closed
+    /// walk starting with the chord from its tail to its head, then returning
+    /// along the tree path. Together, these cycles form a basis of the graph's
+    /// cycle space. O(V log V + total cycle length).
+    pub fn fundamental_cycles(&self, tree_edges: &[usize]) -> Vec<Vec<usize>> {
+        let tree = RootedTree::new(self, tree_edges);
+        let mut in_tree = vec![false; self.num_e() / 2];
+        for &e in tree_edges {
+            in_tree[e] = true;
+        }
+        (0..self.num_e() / 2)
+            .filter(|&e| !in_tree[e])
+            .map(|e| {
+                let (u, v) = (self.endp[2 * e + 1], self.endp[2 * e]);
+                let a = tree.lca(u, v);
+                let mut cycle = vec![e];
+                cycle.extend(tree.path_up(v, a));
+                let mut down = tree.path_up(u, a);
+                down.reverse();
+                cycle.extend(down);
+                cycle
+            })
+            .collect()
+    }
+}
+
+/// Computes the determinant of an integer matrix exactly, by Bareiss's
+/// fraction-free elimination, in which every intermediate entry is the
+/// determinant of a minor. O(n^3).
+///
+/// # Panics
+///
+/// Panics if an intermediate value overflows an i128.
+fn det_bareiss(mut mat: Vec<Vec<i128>>) -> i128 {
+    let n = mat.len();
+    let (mut sign, mut prev) = (1, 1);
+    for k in 0..n {
+        let Some(pivot) = (k..n).find(|&i| mat[i][k] != 0) else {
+            return 0;
+        };
+        if pivot != k {
+            mat.swap(pivot, k);
+            sign = -sign;
+        }
+        let (top, bottom) = mat.split_at_mut(k + 1);
+        let row_k = &top[k];
+        for row in bottom {
+            for j in k + 1..n {
+                let cross = row[j]
+                    .checked_mul(row_k[k])
+                    .and_then(|x| x.checked_sub(row[k].checked_mul(row_k[j])?))
+                    .expect("determinant overflow");
+                row[j] = cross / prev;
+            }
+        }
+        prev = mat[k][k];
+    }
+    sign * if n == 0 { 1 } else { mat[n - 1][n - 1] }
+}
+
+/// Finds a minimum spanning tree of the complete graph on the given points,
+/// weighted by Euclidean distance, as a list of point index pairs (u, v) in
+/// which u joined the tree before v. Runs Prim's algorithm on the implicit
+/// dense graph, in O(n^2) time and O(n) memory.
+pub fn euclidean_mst(points: &[(f64, f64)]) -> Vec<(usize, usize)> {
+    let n = points.len();
+    let sq_dist = |u: usize, v: usize| {
+        let (dx, dy) = (points[u].0 - points[v].0, points[u].1 - points[v].1);
+        dx * dx + dy * dy
+    };
+    // For each point outside the tree, best holds its nearest tree point and
+    // the squared distance between them.
+    let mut best = vec![(0, f64::INFINITY); n];
+    let mut in_tree = vec![false; n];
+    let mut edges = Vec::with_capacity(n.saturating_sub(1));
+    let mut u = 0;
+    for _ in 1..n {
+        in_tree[u] = true;
+        let mut next = None;
+        for v in (0..n).filter(|&v| !in_tree[v]) {
+            let d = sq_dist(u, v);
+            if d < best[v].1 {
+                best[v] = (u, d);
+            }
+            if next.is_none_or(|w: usize| best[v].1 < best[w].1) {
+                next = Some(v);
+            }
+        }
+        u = next.unwrap();
+        edges.push((best[u].0, u));
+    }
+    edges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::graph::xorshift;
+
+    #[test]
+    fn test_verify_mst() {
+        let mut graph = Graph::new(4, 10);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 0);
+        graph.add_undirected_edge(0, 2);
+        let weights = [1, 2, 3, 4, 5];
+
+        let mst = graph.min_spanning_tree(&weights);
+        assert_eq!(graph.verify_mst(&weights, &mst), Ok(()));
+
+        // Replacing edge 2 by the heavier edge 3 leaves 2 as a better option.
+        assert_eq!(
+            graph.verify_mst(&weights, &[0, 1, 3]),
+            Err(MstError::NotMinimal(2))
+        );
+
+        assert_eq!(
+            graph.verify_mst(&weights, &[0, 1]),
+            Err(MstError::NotSpanningTree)
+        );
+        assert_eq!(
+            graph.verify_mst(&weights, &[0, 1, 4]),
+            Err(MstError::NotSpanningTree)
+        );
+    }
+
+    #[test]
+    fn test_verify_mst_random() {
+        let mut rand = xorshift(0x9e37_79b9_7f4a_7c15);
+        let mut graph = Graph::new(30, 240);
+        for u in 1..30 {
+            graph.add_undirected_edge(u, rand() as usize % u);
+        }
+        for _ in 0..91 {
+            graph.add_undirected_edge(rand() as usize % 30, rand() as usize % 30);
+        }
+        let weights = (0..120).map(|_| rand() as i64 % 50).collect::<Vec<_>>();
+        let cost = |tree: &[usize]| tree.iter().map(|&e| weights[e]).sum::<i64>();
+        let mst_cost = cost(&graph.min_spanning_tree(&weights));
+
+        // A spanning tree passes verification iff its cost is minimum.
+        for _ in 0..20 {
+            let fake_weights = (0..120).map(|_| rand() as i64 % 50).collect::<Vec<_>>();
+            let tree = graph.min_spanning_tree(&fake_weights);
+            let result = graph.verify_mst(&weights, &tree);
+            assert_eq!(result.is_ok(), cost(&tree) == mst_cost);
+        }
+    }
+
+    #[test]
+    fn test_count_mst() {
+        // Edges 1 and 2 weigh the same, and either can join vertex 2.
+        let mut triangle = Graph::new(3, 6);
+        triangle.add_undirected_edge(0, 1);
+        triangle.add_undirected_edge(1, 2);
+        triangle.add_undirected_edge(2, 0);
+        assert_eq!(triangle.count_mst(&[1, 2, 2]), 2);
+        assert_eq!(triangle.count_mst(&[1, 2, 3]), 1);
+        assert_eq!(triangle.count_mst(&[5, 5, 5]), 3);
+
+        // By Cayley's formula, K5 has 5^3 spanning trees.
+        let mut complete = Graph::new(5, 20);
+        for u in 0..5 {
+            for v in u + 1..5 {
+                complete.add_undirected_edge(u, v);
+            }
+        }
+        assert_eq!(complete.count_mst(&[0; 10]), 125);
+
+        // A doubled edge in a forest: 2 ways to pick 0-1, and 2-3 is fixed.
+        let mut forest = Graph::new(4, 6);
+        forest.add_undirected_edge(0, 1);
+        forest.add_undirected_edge(1, 0);
+        forest.add_undirected_edge(2, 3);
+        assert_eq!(forest.count_mst(&[4, 4, 1]), 2);
+        assert_eq!(Graph::new(2, 0).count_mst(&[]), 1);
+    }
+
+    #[test]
+    fn test_fundamental_cycles() {
+        let mut graph = Graph::new(7, 20);
+        graph.add_undirected_edge(0, 1);
+        graph.add_undirected_edge(1, 2);
+        graph.add_undirected_edge(2, 0);
+        graph.add_undirected_edge(2, 3);
+        graph.add_undirected_edge(3, 4);
+        graph.add_undirected_edge(4, 1);
+        graph.add_undirected_edge(4, 4);
+        graph.add_undirected_edge(5, 6);
+        graph.add_undirected_edge(6, 5);
+        let tree = graph.min_spanning_tree(&[1; 9]);
+        let mut components = DisjointSets::new(7);
+        for e in 0..9 {
+            components.merge(graph.endp[2 * e], graph.endp[2 * e + 1]);
+        }
+
+        let cycles = graph.fundamental_cycles(&tree);
+        assert_eq!(cycles.len(), 9 - (7 - components.num_sets()));
+        for cycle in &cycles {
+            // Follow the cycle as a closed walk from the chord's tail.
+            let start = graph.endp[2 * cycle[0] + 1];
+            let mut u = start;
+            for &e in cycle {
+                let (a, b) = (graph.endp[2 * e + 1], graph.endp[2 * e]);
+                assert!(u == a || u == b);
+                u = a + b - u;
+            }
+            assert_eq!(u, start);
+            assert!(cycle[1..].iter().all(|e| tree.contains(e)));
+        }
+        assert!(cycles.contains(&vec![6]));
+    }
+
+    #[test]
+    fn test_euclidean_mst() {
+        let square = [(0.0, 0.0), (1.0, 1.0), (0.0, 1.0), (1.0, 0.0)];
+        let edges = euclidean_mst(&square);
+        assert_eq!(edges.len(), 3);
+        let mut components = DisjointSets::new(4);
+        for &(u, v) in &edges {
+            let (dx, dy): (f64, f64) = (square[u].0 - square[v].0, square[u].1 - square[v].1);
+            assert_eq!(dx.hypot(dy), 1.0);
+            assert!(components.merge(u, v));
+        }
+
+        let line = [(0.0, 0.0), (5.0, 0.0), (2.0, 0.0), (3.0, 0.0)];
+        assert_eq!(euclidean_mst(&line), vec![(0, 2), (2, 3), (3, 1)]);
+        assert!(euclidean_mst(&[(1.0, 2.0)]).is_empty());
+        assert!(euclidean_mst(&[]).is_empty());
+    }
+}
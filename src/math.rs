@@ -17,6 +17,37 @@ pub fn mod_pow(mut base: u64, mut exp: u64, m: u64) -> u64 {
     result
 }
 
+/// Computes the determinant of a square matrix modulo a prime p, by Gaussian
+/// elimination in O(n^3). Entries are assumed to be already reduced mod p.
+///
+/// # Panics
+///
+/// Panics if p == 0. May panic on overflow if p * p > 2^63.
+pub fn det_mod(mut mat: Vec<Vec<u64>>, p: u64) -> u64 {
+    let n = mat.len();
+    let mut det = 1 % p;
+    for col in 0..n {
+        let pivot = match (col..n).find(|&row| mat[row][col] != 0) {
+            Some(pivot) => pivot,
+            None => return 0,
+        };
+        if pivot != col {
+            mat.swap(pivot, col);
+            det = (p - det) % p;
+        }
+        det = det * mat[col][col] % p;
+        let inv = mod_pow(mat[col][col], p - 2, p);
+        let (top, bottom) = mat.split_at_mut(col + 1);
+        for row in bottom {
+            let factor = row[col] * inv % p;
+            for (x, &y) in row.iter_mut().zip(&top[col]).skip(col) {
+                *x = (*x + p - factor * y % p) % p;
+            }
+        }
+    }
+    det
+}
+
 /// Finds (d, coef_a, coef_b) such that d = gcd(a, b) = a * coef_a + b * coef_b.
 pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
     if b == 0 {
@@ -44,6 +75,51 @@ pub fn canon_egcd(a: i64, b: i64, c: i64) -> Option<(i64, i64, i64)> {
     }
 }
 
+/// Linear basis of a subspace of GF(2)^64, i.e., a set of 64-bit integers
+/// closed under XOR. Supports finding the minimum or maximum XOR of a given
+/// value with any element of the subspace.
+pub struct XorBasis {
+    /// basis[i] is either 0 or a vector whose highest set bit is i.
+    basis: [u64; 64],
+}
+
+impl Default for XorBasis {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl XorBasis {
+    /// Initializes the basis of the zero subspace.
+    pub fn new() -> Self {
+        Self { basis: [0; 64] }
+    }
+
+    /// Adds x to the subspace. Returns true if x wasn't already in it, in
+    /// which case the dimension increases by 1. O(64).
+    pub fn insert(&mut self, mut x: u64) -> bool {
+        while x != 0 {
+            let i = 63 - x.leading_zeros() as usize;
+            if self.basis[i] == 0 {
+                self.basis[i] = x;
+                return true;
+            }
+            x ^= self.basis[i];
+        }
+        false
+    }
+
+    /// Finds the minimum of x ^ y over all y in the subspace. O(64).
+    pub fn min_with(&self, x: u64) -> u64 {
+        self.basis.iter().rev().fold(x, |x, &b| x.min(x ^ b))
+    }
+
+    /// Finds the maximum of x ^ y over all y in the subspace. O(64).
+    pub fn max_with(&self, x: u64) -> u64 {
+        self.basis.iter().rev().fold(x, |x, &b| x.max(x ^ b))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -59,6 +135,18 @@ mod test {
         assert_eq!(identity, 1);
     }
 
+    #[test]
+    fn test_det_mod() {
+        let p = 1_000_000_007;
+        let mat = vec![vec![0, 2, 1], vec![3, 1, 4], vec![5, 9, 2]];
+        assert_eq!(det_mod(mat, p), 50);
+        assert_eq!(det_mod(vec![vec![0, 1], vec![1, 0]], p), p - 1);
+
+        let singular = vec![vec![1, 2], vec![2, 4]];
+        assert_eq!(det_mod(singular, p), 0);
+        assert_eq!(det_mod(vec![], p), 1);
+    }
+
     #[test]
     fn test_egcd() {
         let (a, b) = (14, 35);
@@ -70,4 +158,21 @@ mod test {
         assert_eq!(canon_egcd(a, b, d), Some((d, -2, 1)));
         assert_eq!(canon_egcd(b, a, d), Some((d, -1, 3)));
     }
+
+    #[test]
+    fn test_xor_basis() {
+        let mut basis = XorBasis::new();
+        assert_eq!(basis.min_with(5), 5);
+        assert!(basis.insert(0b1100));
+        assert!(basis.insert(0b1010));
+        assert!(!basis.insert(0b0110));
+        assert!(!basis.insert(0));
+
+        // The subspace is {0b0000, 0b0110, 0b1010, 0b1100}.
+        assert_eq!(basis.min_with(0b1111), 0b0011);
+        assert_eq!(basis.max_with(0b0001), 0b1101);
+        assert_eq!(basis.max_with(0), 0b1100);
+        assert!(basis.insert(1 << 63));
+        assert_eq!(basis.max_with(0), (1 << 63) | 0b1100);
+    }
 }
@@ -100,7 +100,7 @@ impl SuffixArray {
             let mut cur_rank = prev_rank.clone();
 
             let pos = (n - skip..n).chain(sfx.into_iter().filter_map(|p| p.checked_sub(skip)));
-            sfx = Self::counting_sort(pos, &prev_rank, n.max(256));
+            sfx = Self::counting_sort(pos, prev_rank, n.max(256));
 
             let mut prev = sfx[0];
             cur_rank[prev] = 0;
@@ -183,7 +183,7 @@ pub fn palindromes(text: &[u8]) -> Vec<usize> {
     while pal.len() < pal.capacity() {
         let i = pal.len() - 1;
         let max_len = (i + 1).min(pal.capacity() - i);
-        while pal[i] < max_len && text[(i - pal[i] - 1) / 2] == text[(i + pal[i] + 1) / 2] {
+        while pal[i] < max_len && text[(i - pal[i] - 1) / 2] == text[(i + pal[i]).div_ceil(2)] {
             pal[i] += 2;
         }
         if pal[i] < 2 {